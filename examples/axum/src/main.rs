@@ -1,5 +1,5 @@
 use anyhow::Context;
-use axum::extract::Path;
+use axum::extract::{Path, Query};
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
 use axum::routing;
@@ -12,6 +12,7 @@ use serde::Serialize;
 use sqlx::sqlite::SqlitePool;
 use sqlx::sqlite::SqlitePoolOptions;
 use sqlx::FromRow;
+use sqlx_crud::select::Sort;
 use sqlx_crud::Crud;
 use sqlx_crud::SqlxCrud;
 
@@ -23,15 +24,40 @@ struct Task {
     pub task: String,
 }
 
-async fn tasks(Extension(pool): Extension<SqlitePool>) -> Response {
-    match Task::all(&pool).await {
+#[derive(Deserialize)]
+struct TasksParams {
+    /// Comma separated columns, e.g. `task,-id`.
+    sort: Option<String>,
+    limit: Option<u64>,
+    offset: Option<u64>,
+}
+
+async fn tasks(
+    Query(params): Query<TasksParams>,
+    Extension(pool): Extension<SqlitePool>,
+) -> Response {
+    let mut query = Task::query();
+    if let Some(sort) = params.sort {
+        match Sort::parse(&sort) {
+            Ok(sort) => query = query.sort(sort),
+            Err(e) => return (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+        }
+    }
+    if let Some(limit) = params.limit {
+        query = query.limit(limit);
+    }
+    if let Some(offset) = params.offset {
+        query = query.offset(offset);
+    }
+
+    match query.fetch_all(&pool).await {
         Ok(tasks) => (StatusCode::OK, Json(tasks)).into_response(),
         Err(_) => (StatusCode::INTERNAL_SERVER_ERROR).into_response(),
     }
 }
 
 async fn new_task(Extension(pool): Extension<SqlitePool>, Json(new_task): Json<Task>) -> Response {
-    let new_id = new_task.id.clone();
+    let new_id = new_task.id;
     match new_task.create(&pool).await {
         Ok(r) => {
             assert_eq!(1, r.rows_affected());
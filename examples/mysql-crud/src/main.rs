@@ -4,7 +4,7 @@ use sqlx::{
     types::chrono::{DateTime, Local},
     FromRow, MySql, Pool,
 };
-use sqlx_crud::{add_timed_fields, Crud, SqlxCrud};
+use sqlx_crud::{timestamps, Crud, SqlxCrud};
 use std::{env, str::FromStr, time::Duration};
 
 #[derive(FromRow, SqlxCrud)]
@@ -12,7 +12,7 @@ struct Record {
     #[auto_increment]
     record_id: i64,
     str_field: String,
-    #[ignore_when(insert)]
+    #[updated_at]
     updated_at: Option<DateTime<Local>>,
 }
 #[derive(Debug, FromRow, SqlxCrud, Default)]
@@ -20,15 +20,15 @@ struct Record {
 struct MoreFields {
     more_field_id: i64,
     str_field: String,
-    #[ignore_when(insert, update)]
+    #[created_at]
     created_at: Option<DateTime<Local>>,
-    #[ignore_when(insert, update)]
+    #[updated_at]
     updated_at: Option<DateTime<Local>>,
     #[ignore_when(insert, update)]
     deleted_at: Option<DateTime<Local>>,
 }
 use serde::Serialize;
-#[add_timed_fields]
+#[timestamps]
 #[derive(Debug, Clone, FromRow, SqlxCrud, Serialize, Default)]
 struct TimedField {
     timed_field_id: i64,
@@ -46,7 +46,7 @@ async fn db_conn() -> anyhow::Result<Pool<MySql>> {
         db_user, db_password, db_host, db_port, db_databse
     );
     println!("db_url: {}", db_url);
-    let conn_opts = MySqlConnectOptions::from_str(&db_url)?;
+    let conn_opts = MySqlConnectOptions::from_str(db_url)?;
     let pool = MySqlPoolOptions::new()
         .min_connections(5)
         .connect_with(conn_opts)
@@ -61,10 +61,10 @@ async fn test_record(pool: &Pool<MySql>) -> anyhow::Result<()> {
         updated_at: None,
     };
     let record_id = record.record_id;
-    let r = record.create(&pool).await?;
+    let r = record.create(pool).await?;
     assert_eq!(1, r.rows_affected());
 
-    let record = Record::by_id(&pool, record_id).await?;
+    let record = Record::by_id(pool, record_id).await?;
     match record {
         Some(record) => match record.updated_at {
             Some(upd_at) => println!("{}", upd_at),
@@ -81,17 +81,17 @@ async fn test_more_fields(pool: &Pool<MySql>) -> anyhow::Result<()> {
         str_field: "hello".to_string(),
         ..Default::default()
     };
-    let r = frecord.create(&pool).await?;
+    let r = frecord.create(pool).await?;
     assert_eq!(1, r.rows_affected());
-    let mut frecord = MoreFields::by_id(&pool, 16).await?.unwrap();
+    let mut frecord = MoreFields::by_id(pool, 16).await?.unwrap();
     println!("{:?}", frecord);
     frecord.str_field = "world".to_string();
     std::thread::sleep(Duration::from_secs(2));
-    let r = frecord.update(&pool).await?;
+    let r = frecord.update(pool).await?;
     assert_eq!(1, r.rows_affected());
-    let frecord = MoreFields::by_id(&pool, 16).await?.unwrap();
+    let frecord = MoreFields::by_id(pool, 16).await?.unwrap();
     println!("{:?}", frecord);
-    let r = frecord.delete(&pool).await?;
+    let r = frecord.delete(pool).await?;
     assert_eq!(1, r.rows_affected());
     Ok(())
 }
@@ -107,7 +107,7 @@ async fn test_timed_fields(pool: &Pool<MySql>) -> anyhow::Result<()> {
     println!("to json: {}", serde_json::to_string(&record).unwrap());
     let record = TimedField::by_id(pool, 21).await?.unwrap();
     println!("to json: {}", serde_json::to_string(&record).unwrap());
-    let r = record.delete(&pool).await?;
+    let r = record.delete(pool).await?;
     assert_eq!(1, r.rows_affected());
     Ok(())
 }
@@ -2,33 +2,70 @@ use inflector::Inflector;
 use proc_macro::{self, TokenStream};
 use proc_macro2::TokenStream as TokenStream2;
 use quote::{format_ident, quote};
+use syn::ext::IdentExt;
 use syn::punctuated::Punctuated;
 use syn::token::Comma;
 use syn::{
     parse_macro_input, Attribute, Data, DataStruct, DeriveInput, Expr, ExprLit, Field, Fields,
-    FieldsNamed, Ident, LitStr, Token,
+    FieldsNamed, GenericArgument, Ident, LitStr, PathArguments, Token, Type, TypePath, Visibility,
 };
 
+mod relations;
+mod timestamps;
+
+use relations::{build_relations, HasMany, ManyToMany};
+use timestamps::Timestamps;
+
 #[proc_macro_derive(
     SqlxCrud,
-    attributes(database, external_id, id, ignore_when, auto_increment, deleted_with,)
+    attributes(
+        belongs_to,
+        crud,
+        database,
+        external_id,
+        has_many,
+        hooks,
+        id,
+        ignore_when,
+        many_to_many,
+        auto_increment,
+        deleted_with,
+        deleted_by,
+        created_at,
+        updated_at,
+        index,
+        table_name,
+        validate,
+    )
 )]
 pub fn derive(input: TokenStream) -> TokenStream {
     let DeriveInput {
-        ident, data, attrs, ..
+        ident,
+        vis,
+        data,
+        attrs,
+        ..
     } = parse_macro_input!(input);
     match data {
         Data::Struct(DataStruct {
             fields: Fields::Named(FieldsNamed { named, .. }),
             ..
         }) => {
-            let config = Config::new(&attrs, &ident, &named);
+            let config = Config::new(&attrs, &vis, &ident, &named);
             let static_model_schema = build_static_model_schema(&config);
+            let model_registration = build_model_registration(&config);
             let sqlx_crud_impl = build_sqlx_crud_impl(&config);
+            let from_join_row = build_from_join_row(&config);
+            let columns = build_columns(&config);
+            let relations = build_relations(&config);
 
             quote! {
                 #static_model_schema
+                #model_registration
                 #sqlx_crud_impl
+                #from_join_row
+                #columns
+                #relations
             }
             .into()
         }
@@ -36,39 +73,240 @@ pub fn derive(input: TokenStream) -> TokenStream {
     }
 }
 
+/// Adds `created_at`, `updated_at` and `deleted_at` fields to a struct
+/// deriving `SqlxCrud`. It has to be placed before the derive.
+#[proc_macro_attribute]
+pub fn timestamps(args: TokenStream, input: TokenStream) -> TokenStream {
+    let mut timestamps = Timestamps::default();
+    let parser = syn::meta::parser(|meta| timestamps.parse(meta));
+    parse_macro_input!(args with parser);
+
+    timestamps.expand(parse_macro_input!(input)).into()
+}
+
 fn build_static_model_schema(config: &Config) -> TokenStream2 {
     let crate_name = &config.crate_name;
     let model_schema_ident = &config.model_schema_ident;
     let table_name = &config.table_name;
 
-    let id_column = config.id_column_ident.to_string();
+    let id_column = &config.id_column;
     let columns_len = config.named.iter().count();
-    let columns = config
-        .named
-        .iter()
-        .flat_map(|f| &f.ident)
-        .map(|f| LitStr::new(format!("{}", f).as_str(), f.span()));
+    let columns = config.named.iter().map(column_name);
+
+    let dialect = config.db_ty.dialect(crate_name);
+    let column_meta = build_column_meta(config);
+    let indexes = build_indexes(config);
+    let audited = config.audited;
+    let outbox = config.outbox;
+    let events = config.events;
 
     let sql_queries = build_sql_queries(config);
 
     quote! {
         #[automatically_derived]
         static #model_schema_ident: #crate_name::schema::Metadata<'static, #columns_len> = #crate_name::schema::Metadata {
+            dialect: #dialect,
             table_name: #table_name,
             id_column: #id_column,
             columns: [#(#columns),*],
+            column_meta: [#(#column_meta),*],
+            indexes: &[#(#indexes),*],
+            audited: #audited,
+            outbox: #outbox,
+            events: #events,
             #sql_queries
         };
     }
 }
 
+fn build_model_registration(config: &Config) -> TokenStream2 {
+    let crate_name = &config.crate_name;
+    let model_schema_ident = &config.model_schema_ident;
+    let name = config.ident.to_string();
+
+    quote! {
+        #crate_name::inventory::submit! {
+            #crate_name::registry::Model {
+                name: #name,
+                dialect: #model_schema_ident.dialect,
+                table_name: #model_schema_ident.table_name,
+                id_column: #model_schema_ident.id_column,
+                columns: &#model_schema_ident.columns,
+                column_meta: &#model_schema_ident.column_meta,
+                indexes: #model_schema_ident.indexes,
+                select_sql: #model_schema_ident.select_sql,
+                select_by_id_sql: #model_schema_ident.select_by_id_sql,
+                insert_sql: #model_schema_ident.insert_sql,
+                update_by_id_sql: #model_schema_ident.update_by_id_sql,
+                delete_by_id_sql: #model_schema_ident.delete_by_id_sql,
+                create_table_sql: #model_schema_ident.create_table_sql,
+                drop_table_sql: #model_schema_ident.drop_table_sql,
+            }
+        }
+    }
+}
+
+fn build_column_meta(config: &Config) -> Vec<TokenStream2> {
+    let crate_name = &config.crate_name;
+
+    config
+        .named
+        .iter()
+        .map(|f| {
+            let ident = f.ident.as_ref().unwrap();
+            let name = column_name(f);
+            let rust_type = type_name(&f.ty);
+            let sql_type = config.db_ty.sql_type(&f.ty);
+            let definition = build_column_definition(config, f);
+            let nullable = is_option(&f.ty);
+            let is_id = ident == &config.id_column_ident;
+            let auto_increment = is_id && config.id_auto_increment;
+            let insertable = config.is_insertable(f);
+            let updatable = config.is_updatable(f);
+            let soft_delete = config.is_soft_delete(f);
+
+            quote! {
+                #crate_name::schema::ColumnMeta {
+                    name: #name,
+                    rust_type: #rust_type,
+                    sql_type: #sql_type,
+                    definition: #definition,
+                    nullable: #nullable,
+                    is_id: #is_id,
+                    auto_increment: #auto_increment,
+                    insertable: #insertable,
+                    updatable: #updatable,
+                    soft_delete: #soft_delete,
+                }
+            }
+        })
+        .collect()
+}
+
+fn build_indexes(config: &Config) -> Vec<TokenStream2> {
+    let crate_name = &config.crate_name;
+    let table_name = config.quote_ident(&config.table_name);
+
+    config
+        .named
+        .iter()
+        .filter_map(|f| Config::index(f).map(|unique| (f, unique)))
+        .map(|(f, unique)| {
+            let column = column_name(f);
+            let name = format!("idx_{}_{}", config.table_name, column);
+            let create_sql = format!(
+                "CREATE {}INDEX {} ON {} ({})",
+                if unique { "UNIQUE " } else { "" },
+                config.quote_ident(&name),
+                table_name,
+                config.quote_ident(&column)
+            );
+
+            quote! {
+                #crate_name::schema::IndexMeta {
+                    name: #name,
+                    column: #column,
+                    unique: #unique,
+                    create_sql: #create_sql,
+                }
+            }
+        })
+        .collect()
+}
+
+/// Renders a type the way it would be written in source, e.g.
+/// `Option<DateTime<Local>>` rather than `Option < DateTime < Local > >`.
+fn type_name(ty: &Type) -> String {
+    let tokens = quote!(#ty).to_string();
+    let chars = tokens
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+        .collect::<Vec<_>>();
+    let is_word = |c: Option<&char>| c.is_some_and(|c| c.is_alphanumeric() || *c == '_');
+
+    chars
+        .iter()
+        .enumerate()
+        .filter(|(i, c)| {
+            **c != ' ' || (is_word(chars.get(i.wrapping_sub(1))) && is_word(chars.get(i + 1)))
+        })
+        .map(|(_, c)| c)
+        .collect()
+}
+
+/// The column of a field: the name given with `#[sqlx(rename = "...")]`,
+/// like `FromRow` reads it, or the field name without a raw `r#` prefix.
+fn column_name(field: &Field) -> String {
+    let mut rename = None;
+    for attr in field.attrs.iter().filter(|a| a.path().is_ident("sqlx")) {
+        let _ = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                rename = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.input.peek(Token![=]) {
+                meta.value()?.parse::<Expr>()?;
+            }
+            Ok(())
+        });
+    }
+    rename.unwrap_or_else(|| field.ident.as_ref().unwrap().unraw().to_string())
+}
+
+fn is_option(ty: &Type) -> bool {
+    type_ident(ty).is_some_and(|ident| ident == "Option")
+}
+
+fn is_timestamp(ty: &Type) -> bool {
+    type_ident(strip_option(ty)).is_some_and(|ident| {
+        ident == "DateTime"
+            || ident == "NaiveDateTime"
+            || ident == "OffsetDateTime"
+            || ident == "PrimitiveDateTime"
+    })
+}
+
+/// Returns the last path segment of a type, e.g. `DateTime` for
+/// `chrono::DateTime<Local>`.
+fn type_ident(ty: &Type) -> Option<&Ident> {
+    match ty {
+        Type::Path(TypePath { path, .. }) => path.segments.last().map(|s| &s.ident),
+        _ => None,
+    }
+}
+
+/// Returns the first generic type argument of a type, e.g. `T` for `Vec<T>`.
+fn type_argument(ty: &Type) -> Option<&Type> {
+    match ty {
+        Type::Path(TypePath { path, .. }) => match &path.segments.last()?.arguments {
+            PathArguments::AngleBracketed(args) => args.args.iter().find_map(|arg| match arg {
+                GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            }),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn is_bytes(ty: &Type) -> bool {
+    type_argument(ty)
+        .and_then(type_ident)
+        .is_some_and(|ident| ident == "u8")
+}
+
+/// Unwraps `Option<T>` to `T`, other types are returned as is.
+fn strip_option(ty: &Type) -> &Type {
+    if is_option(ty) {
+        type_argument(ty).unwrap_or(ty)
+    } else {
+        ty
+    }
+}
+
 fn build_sql_queries(config: &Config) -> TokenStream2 {
     let table_name = config.quote_ident(&config.table_name);
-    let id_column = format!(
-        "{}.{}",
-        &table_name,
-        config.quote_ident(&config.id_column_ident.to_string())
-    );
+    let id_column = format!("{}.{}", &table_name, config.quote_ident(&config.id_column));
 
     // build select sql
     let (select_sql, select_by_id_sql) = build_select_sql(config, &table_name, &id_column);
@@ -78,37 +316,45 @@ fn build_sql_queries(config: &Config) -> TokenStream2 {
     let update_by_id_sql = build_update_sql(config, &table_name, &id_column);
     // build delete sql
     let delete_by_id_sql = build_delete_sql(config, &table_name, &id_column);
+    let force_delete_by_id_sql = format!("DELETE FROM {} WHERE {} = ?", table_name, id_column);
+    // build soft delete sql
+    let soft_delete_sql = build_soft_delete_sql(config, &table_name, &id_column);
+    // build ddl
+    let create_table_sql = build_create_table_sql(config, &table_name);
+    let drop_table_sql = format!("DROP TABLE IF EXISTS {}", table_name);
     quote! {
         select_sql: #select_sql,
         select_by_id_sql: #select_by_id_sql,
         insert_sql: #insert_sql,
         update_by_id_sql: #update_by_id_sql,
         delete_by_id_sql: #delete_by_id_sql,
+        force_delete_by_id_sql: #force_delete_by_id_sql,
+        #soft_delete_sql
+        create_table_sql: #create_table_sql,
+        drop_table_sql: #drop_table_sql,
     }
 }
 
-fn build_select_sql(config: &Config, table_name: &String, id_column: &String) -> (String, String) {
-    let column_list = config
+fn build_column_list(config: &Config, table_name: &String) -> String {
+    config
         .named
         .iter()
-        .flat_map(|f| &f.ident)
-        .map(|i| format!("{}.{}", &table_name, config.quote_ident(&i.to_string())))
+        .map(|f| format!("{}.{}", &table_name, config.quote_ident(&column_name(f))))
         .collect::<Vec<_>>()
-        .join(", ");
-    match config.delete_ident() {
-        Some(ident) => {
+        .join(", ")
+}
+
+fn build_select_sql(config: &Config, table_name: &String, id_column: &String) -> (String, String) {
+    let column_list = build_column_list(config, table_name);
+    match config.not_deleted_sql() {
+        Some(not_deleted) => {
             let select_sql = format!(
-                "SELECT {} FROM {} WHERE {} IS NULL",
-                column_list,
-                table_name,
-                config.quote_ident(ident.as_str())
+                "SELECT {} FROM {} WHERE {}",
+                column_list, table_name, not_deleted
             );
             let select_by_id_sql = format!(
-                "SELECT {} FROM {} WHERE {} = ? AND {} IS NULL LIMIT 1",
-                column_list,
-                table_name,
-                id_column,
-                config.quote_ident(ident.as_str())
+                "SELECT {} FROM {} WHERE {} = ? AND {} LIMIT 1",
+                column_list, table_name, id_column, not_deleted
             );
             (select_sql, select_by_id_sql)
         }
@@ -132,34 +378,42 @@ fn build_insert_sql(config: &Config, table_name: &String) -> String {
     let insert_column_list = config
         .insert_fields
         .iter()
-        .flat_map(|f| &f.ident)
         // .filter(|i| config.external_id || *i != &config.id_column_ident)
-        .map(|i| config.quote_ident(&i.to_string()))
+        .map(|f| config.quote_ident(&column_name(f)))
         .collect::<Vec<_>>()
         .join(", ");
-    format!(
-        "INSERT INTO {} ({}) VALUES ({})",
-        table_name, insert_column_list, insert_sql_binds
-    )
+    // Postgres and MSSQL don't report the primary key assigned by the
+    // database in the query result, so the insert returns it instead.
+    let id_column = config.quote_ident(&config.id_column);
+    match config.db_ty {
+        DbType::Postgres if config.id_auto_increment => format!(
+            "INSERT INTO {} ({}) VALUES ({}) RETURNING {}",
+            table_name, insert_column_list, insert_sql_binds, id_column
+        ),
+        DbType::Mssql if config.id_auto_increment => format!(
+            "INSERT INTO {} ({}) OUTPUT INSERTED.{} VALUES ({})",
+            table_name, insert_column_list, id_column, insert_sql_binds
+        ),
+        _ => format!(
+            "INSERT INTO {} ({}) VALUES ({})",
+            table_name, insert_column_list, insert_sql_binds
+        ),
+    }
 }
 
 fn build_update_sql(config: &Config, table_name: &String, id_column: &String) -> String {
     let update_sql_binds = config
         .update_fields
         .iter()
-        .flat_map(|f| &f.ident)
-        .filter(|i| *i != &config.id_column_ident)
-        .map(|i| format!("{} = ?", config.quote_ident(&i.to_string())))
+        .filter(|f| f.ident.as_ref() != Some(&config.id_column_ident))
+        .map(|f| format!("{} = ?", config.quote_ident(&column_name(f))))
         .collect::<Vec<_>>()
         .join(", ");
 
-    match config.delete_ident() {
-        Some(field) => format!(
-            "UPDATE {} SET {} WHERE {} = ? AND {} IS NULL",
-            table_name,
-            update_sql_binds,
-            id_column,
-            config.quote_ident(field.as_str())
+    match config.not_deleted_sql() {
+        Some(not_deleted) => format!(
+            "UPDATE {} SET {} WHERE {} = ? AND {}",
+            table_name, update_sql_binds, id_column, not_deleted
         ),
         None => format!(
             "UPDATE {} SET {} WHERE {} = ?",
@@ -168,34 +422,389 @@ fn build_update_sql(config: &Config, table_name: &String, id_column: &String) ->
     }
 }
 fn build_delete_sql(config: &Config, table_name: &String, id_column: &String) -> String {
-    config.delete_field.map_or_else(
-        || format!("DELETE FROM {} WHERE {} = ?", table_name, id_column),
-        |field| {
-            let ident = field
+    match (config.delete_field, config.deleted_value()) {
+        (Some(field), Some(deleted)) => format!(
+            "UPDATE {} SET {} = {} WHERE {} = ? AND {}",
+            table_name,
+            config.quote_ident(&column_name(field)),
+            deleted,
+            id_column,
+            config.not_deleted_sql().unwrap()
+        ),
+        _ => format!("DELETE FROM {} WHERE {} = ?", table_name, id_column),
+    }
+}
+
+fn build_soft_delete_sql(config: &Config, table_name: &String, id_column: &String) -> TokenStream2 {
+    let column_list = build_column_list(config, table_name);
+    let select_with_deleted_sql = format!("SELECT {} FROM {}", column_list, table_name);
+
+    let (Some(field), Some(deleted_with)) = (config.delete_field, &config.deleted_with) else {
+        return quote! {
+            select_with_deleted_sql: #select_with_deleted_sql,
+            not_deleted_sql: None,
+            select_only_deleted_sql: None,
+            restore_by_id_sql: None,
+            purge_deleted_sql: None,
+            delete_as_by_id_sql: None,
+            soft_delete_sql: None,
+        };
+    };
+
+    let deleted_field = config.quote_ident(&column_name(field));
+    let deleted = config.deleted_sql().unwrap();
+    let not_deleted = config.not_deleted_sql().unwrap();
+    let select_only_deleted_sql = format!(
+        "SELECT {} FROM {} WHERE {}",
+        column_list, table_name, deleted
+    );
+    let qualified_not_deleted_sql = format!("{}.{}", table_name, not_deleted);
+
+    let mut restore_binds = vec![format!(
+        "{} = {}",
+        deleted_field,
+        match deleted_with {
+            DeletedWith::Flag => config.db_ty.bool_literal(false),
+            _ => "NULL",
+        }
+    )];
+    let delete_as_by_id_sql = config.deleted_by_field.map(|f| {
+        let deleted_by_field = config.quote_ident(&column_name(f));
+        restore_binds.push(format!("{} = NULL", deleted_by_field));
+        format!(
+            "UPDATE {} SET {} = {}, {} = ? WHERE {} = ? AND {}",
+            table_name,
+            deleted_field,
+            config.deleted_value().unwrap(),
+            deleted_by_field,
+            id_column,
+            not_deleted
+        )
+    });
+    let restore_by_id_sql = format!(
+        "UPDATE {} SET {} WHERE {} = ? AND {}",
+        table_name,
+        restore_binds.join(", "),
+        id_column,
+        deleted
+    );
+
+    // A flag doesn't record when a row was deleted, so it can't be purged by age.
+    let purge_deleted_sql = match deleted_with {
+        DeletedWith::Flag => quote! { None },
+        _ => {
+            let sql = format!(
+                "DELETE FROM {} WHERE {} AND {} < ?",
+                table_name, deleted, deleted_field
+            );
+            quote! { Some(#sql) }
+        }
+    };
+    let delete_as_by_id_sql = match delete_as_by_id_sql {
+        Some(sql) => quote! { Some(#sql) },
+        None => quote! { None },
+    };
+    let soft_delete_sql = format!(
+        "UPDATE {} SET {} = {}",
+        table_name,
+        deleted_field,
+        config.deleted_value().unwrap()
+    );
+
+    quote! {
+        select_with_deleted_sql: #select_with_deleted_sql,
+        not_deleted_sql: Some(#qualified_not_deleted_sql),
+        select_only_deleted_sql: Some(#select_only_deleted_sql),
+        restore_by_id_sql: Some(#restore_by_id_sql),
+        purge_deleted_sql: #purge_deleted_sql,
+        delete_as_by_id_sql: #delete_as_by_id_sql,
+        soft_delete_sql: Some(#soft_delete_sql),
+    }
+}
+
+fn build_create_table_sql(config: &Config, table_name: &String) -> String {
+    let column_definitions = config
+        .named
+        .iter()
+        .map(|f| build_column_definition(config, f))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    format!(
+        "CREATE TABLE IF NOT EXISTS {} ({})",
+        table_name, column_definitions
+    )
+}
+
+fn build_column_definition(config: &Config, field: &Field) -> String {
+    let ident = field.ident.as_ref().unwrap();
+    let column = config.quote_ident(&column_name(field));
+    let is_id = ident == &config.id_column_ident;
+
+    if is_id && config.id_auto_increment {
+        return format!(
+            "{} {}",
+            column,
+            config.db_ty.auto_increment_column(&field.ty)
+        );
+    }
+
+    let null = if is_option(&field.ty) {
+        "NULL"
+    } else {
+        "NOT NULL"
+    };
+    let mut definition = format!("{} {} {}", column, config.db_ty.sql_type(&field.ty), null);
+
+    if is_id {
+        definition.push_str(" PRIMARY KEY");
+    } else if config.is_soft_delete(field) && matches!(config.deleted_with, Some(DeletedWith::Flag))
+    {
+        definition.push_str(" DEFAULT ");
+        definition.push_str(config.db_ty.bool_literal(false));
+    } else if is_timestamp(&field.ty)
+        && !config.is_soft_delete(field)
+        && !config.is_insertable(field)
+    {
+        // Timestamps the database is expected to fill in, tagged with
+        // #[ignore_when(insert)].
+        definition.push_str(" DEFAULT ");
+        definition.push_str(config.db_ty.current_timestamp());
+        if matches!(config.db_ty, DbType::MySql)
+            && ident == "updated_at"
+            && !config.is_updatable(field)
+        {
+            definition.push_str(" ON UPDATE CURRENT_TIMESTAMP");
+        }
+    }
+
+    definition
+}
+
+fn build_validate_impl(config: &Config) -> TokenStream2 {
+    let crate_name = &config.crate_name;
+    let ident = &config.ident;
+
+    let field_checks = config
+        .named
+        .iter()
+        .filter_map(|f| {
+            let field = f.ident.as_ref().unwrap();
+            let name = field.to_string();
+            let checks = f
                 .attrs
                 .iter()
-                .find(|attr| attr.path().is_ident("deleted_with"))
-                .unwrap()
-                .meta
-                .require_name_value()
-                .expect("deleted_with must has a value like `#[deleted_with = \"now()\"]`")
-                .clone()
-                .value;
-            let deleted = match ident {
-                Expr::Lit(ExprLit {
-                    lit: syn::Lit::Str(lit_str),
-                    ..
-                }) => lit_str.value(),
-                _ => panic!("deleted_with must be a string"),
+                .filter(|attr| attr.path().is_ident("validate"))
+                .flat_map(|attr| build_validate_checks(crate_name, &name, attr))
+                .collect::<Vec<_>>();
+            if checks.is_empty() {
+                return None;
+            }
+
+            // None values of Option<T> fields aren't checked.
+            Some(if is_option(&f.ty) {
+                quote! {
+                    if let ::std::option::Option::Some(value) = &self.#field {
+                        #(#checks)*
+                    }
+                }
+            } else {
+                quote! {
+                    {
+                        let value = &self.#field;
+                        #(#checks)*
+                    }
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let body = if field_checks.is_empty() {
+        quote! { ::std::result::Result::Ok(()) }
+    } else {
+        quote! {
+            let mut errors = ::std::vec::Vec::new();
+            #(#field_checks)*
+            if errors.is_empty() {
+                ::std::result::Result::Ok(())
+            } else {
+                ::std::result::Result::Err(#crate_name::validate::ValidationErrors::new(errors))
+            }
+        }
+    };
+
+    quote! {
+        #[automatically_derived]
+        impl #crate_name::validate::Validate for #ident {
+            fn validate(&self) -> ::std::result::Result<(), #crate_name::validate::ValidationErrors> {
+                #body
+            }
+        }
+    }
+}
+
+/// Returns the checks of a `#[validate(...)]` attribute of the field `name`.
+/// Each check adds its error to `errors` if the field's `value` fails it.
+fn build_validate_checks(
+    crate_name: &TokenStream2,
+    name: &str,
+    attr: &Attribute,
+) -> Vec<TokenStream2> {
+    let mut checks = Vec::new();
+    attr.parse_nested_meta(|meta| {
+        if meta.path.is_ident("length") || meta.path.is_ident("range") {
+            let none = quote! { ::std::option::Option::None };
+            let (mut min, mut max) = (none.clone(), none);
+            meta.parse_nested_meta(|bound| {
+                let value = bound.value()?.parse::<Expr>()?;
+                if bound.path.is_ident("min") {
+                    min = quote! { ::std::option::Option::Some(#value) };
+                } else if bound.path.is_ident("max") {
+                    max = quote! { ::std::option::Option::Some(#value) };
+                } else {
+                    return Err(bound.error("expected min or max"));
+                }
+                Ok(())
+            })?;
+            let check = meta.path.get_ident();
+            checks.push(quote! {
+                errors.extend(#crate_name::validate::#check(#name, value, #min, #max));
+            });
+        } else if meta.path.is_ident("regex") {
+            let pattern = meta.value()?.parse::<LitStr>()?;
+            // The pattern is compiled here so a typo fails the build at the
+            // attribute instead of the first validate() call.
+            if let Err(e) = regex::Regex::new(&pattern.value()) {
+                let message = format!("invalid #[validate(regex)] pattern: {}", e);
+                checks.push(syn::Error::new_spanned(&pattern, message).to_compile_error());
+                return Ok(());
+            }
+            checks.push(quote! {
+                {
+                    static REGEX: ::std::sync::OnceLock<#crate_name::validate::Regex> =
+                        ::std::sync::OnceLock::new();
+                    let regex = REGEX.get_or_init(|| {
+                        #crate_name::validate::Regex::new(#pattern).expect("checked by #[derive(SqlxCrud)]")
+                    });
+                    errors.extend(#crate_name::validate::regex(#name, value, regex));
+                }
+            });
+        } else {
+            return Err(meta.error(VALIDATE_USAGE));
+        }
+        Ok(())
+    })
+    .unwrap_or_else(|e| panic!("{}: {}", VALIDATE_USAGE, e));
+
+    checks
+}
+
+/// Decodes the model from a joined row by column position, see the select
+/// module. The `#[sqlx(...)]` attributes of the fields are read like
+/// `FromRow` reads them: a renamed column is selected at the field's
+/// position, `try_from` decodes the given type and converts it, and `default`
+/// replaces a missing column. `flatten` is refused, the columns of a
+/// flattened struct aren't columns of the model.
+fn build_from_join_row(config: &Config) -> TokenStream2 {
+    let crate_name = &config.crate_name;
+    let ident = &config.ident;
+    let db_ty = config.db_ty.sqlx_db();
+    let fields = config.named.iter().enumerate().map(|(i, f)| {
+        let field = f.ident.as_ref().unwrap();
+        let ty = &f.ty;
+        let (mut try_from, mut default) = (None, false);
+        for attr in f.attrs.iter().filter(|a| a.path().is_ident("sqlx")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("try_from") {
+                    try_from = Some(meta.value()?.parse::<LitStr>()?.parse::<Type>()?);
+                } else if meta.path.is_ident("default") {
+                    default = true;
+                } else if meta.path.is_ident("flatten") {
+                    return Err(meta.error("#[sqlx(flatten)] fields aren't supported by SqlxCrud"));
+                } else if meta.input.peek(Token![=]) {
+                    meta.value()?.parse::<Expr>()?;
+                }
+                Ok(())
+            })?;
+        }
+
+        let mut value = match try_from {
+            Some(from) => quote! {
+                ::sqlx::Row::try_get::<#from, _>(row, offset + #i).and_then(|value| {
+                    <#ty as ::std::convert::TryFrom<#from>>::try_from(value).map_err(|_| {
+                        ::sqlx::Error::ColumnNotFound("FromRow: try_from failed".to_string())
+                    })
+                })
+            },
+            None => quote! { ::sqlx::Row::try_get::<#ty, _>(row, offset + #i) },
+        };
+        if default {
+            value = quote! {
+                #value.or_else(|e| match e {
+                    ::sqlx::Error::ColumnNotFound(_) => {
+                        ::std::result::Result::Ok(::std::default::Default::default())
+                    }
+                    e => ::std::result::Result::Err(e),
+                })
             };
-            let quoted_deleted_field =
-                config.quote_ident(&field.ident.as_ref().unwrap().to_string());
-            format!(
-                "UPDATE {} SET {} = {} WHERE {} = ? AND {} IS NULL",
-                table_name, quoted_deleted_field, deleted, id_column, quoted_deleted_field
+        }
+        Ok(quote! { #field: #value? })
+    });
+    let fields = match fields.collect::<syn::Result<Vec<_>>>() {
+        Ok(fields) => fields,
+        Err(e) => return e.to_compile_error(),
+    };
+
+    quote! {
+        #[automatically_derived]
+        impl #crate_name::select::FromJoinRow<<#db_ty as ::sqlx::Database>::Row> for #ident {
+            fn from_join_row(
+                row: &<#db_ty as ::sqlx::Database>::Row,
+                offset: usize,
+            ) -> ::std::result::Result<Self, ::sqlx::Error> {
+                ::std::result::Result::Ok(Self { #(#fields),* })
+            }
+        }
+    }
+}
+
+/// Typed column constants of the model, returned by `Model::cols()`.
+fn build_columns(config: &Config) -> TokenStream2 {
+    let crate_name = &config.crate_name;
+    let ident = &config.ident;
+    let vis = config.vis;
+    let columns_ident = format_ident!("{}Columns", ident);
+    let doc = format!("The columns of [`{}`], returned by `{}::cols()`.", ident, ident);
+    let (fields, values): (Vec<_>, Vec<_>) = config
+        .named
+        .iter()
+        .map(|f| {
+            let field = f.ident.as_ref().unwrap();
+            let ty = &f.ty;
+            let name = column_name(f);
+            (
+                quote! { pub #field: #crate_name::select::Column<#ident, #ty> },
+                quote! { #field: #crate_name::select::Column::new(#name) },
             )
-        },
-    )
+        })
+        .unzip();
+
+    quote! {
+        #[doc = #doc]
+        #[derive(Clone, Copy)]
+        #vis struct #columns_ident {
+            #(#fields),*
+        }
+
+        #[automatically_derived]
+        impl #ident {
+            /// Returns the typed columns of the model, e.g. to sort the
+            /// select of `Crud::query`.
+            #vis fn cols() -> #columns_ident {
+                #columns_ident { #(#values),* }
+            }
+        }
+    }
 }
 
 fn build_sqlx_crud_impl(config: &Config) -> TokenStream2 {
@@ -241,11 +850,59 @@ fn build_sqlx_crud_impl(config: &Config) -> TokenStream2 {
         .flat_map(|f| &f.ident)
         .map(|i| quote! { ::sqlx::encode::Encode::<#db_ty>::size_hint(&self.#i) });
 
+    // #[created_at] and #[updated_at] fields are set to the current time
+    // before they are bound.
+    let set_now = |f: &&Field| {
+        let i = &f.ident;
+        let ty = &f.ty;
+        quote! { self.#i = <#ty as #crate_name::timestamp::Now>::now(); }
+    };
+    let insert_timestamps = config
+        .insert_fields
+        .iter()
+        .filter(|f| Config::is_created_at(f) || Config::is_updated_at(f))
+        .map(set_now)
+        .collect::<Vec<_>>();
+    let update_timestamps = config
+        .update_fields
+        .iter()
+        .filter(|f| Config::is_updated_at(f))
+        .map(set_now)
+        .collect::<Vec<_>>();
+    let insert_self = if insert_timestamps.is_empty() {
+        quote! { self }
+    } else {
+        quote! { mut self }
+    };
+    let update_self = if update_timestamps.is_empty() {
+        quote! { self }
+    } else {
+        quote! { mut self }
+    };
+
+    let validate_impl = build_validate_impl(config);
+    let execute_write = build_execute_write(config, id_ty);
+    let publish_event = build_publish_event(config);
+
+    // #[hooks] structs implement CrudHooks themselves.
+    let crud_hooks_impl = if config.hooks {
+        quote! {}
+    } else {
+        quote! {
+            #[automatically_derived]
+            impl<'e> #crate_name::traits::CrudHooks<'e, &'e ::sqlx::pool::Pool<#db_ty>> for #ident {}
+        }
+    };
+
     quote! {
         #[automatically_derived]
         impl #crate_name::traits::Schema for #ident {
             type Id = #id_ty;
 
+            fn dialect() -> #crate_name::schema::Dialect {
+                #model_schema_ident.dialect
+            }
+
             fn table_name() -> &'static str {
                 #model_schema_ident.table_name
             }
@@ -262,6 +919,26 @@ fn build_sqlx_crud_impl(config: &Config) -> TokenStream2 {
                 &#model_schema_ident.columns
             }
 
+            fn column_meta() -> &'static [#crate_name::schema::ColumnMeta<'static>] {
+                &#model_schema_ident.column_meta
+            }
+
+            fn indexes() -> &'static [#crate_name::schema::IndexMeta<'static>] {
+                #model_schema_ident.indexes
+            }
+
+            fn audited() -> bool {
+                #model_schema_ident.audited
+            }
+
+            fn outbox() -> bool {
+                #model_schema_ident.outbox
+            }
+
+            fn events() -> bool {
+                #model_schema_ident.events
+            }
+
             fn select_sql() -> &'static str {
                 #model_schema_ident.select_sql
             }
@@ -281,32 +958,189 @@ fn build_sqlx_crud_impl(config: &Config) -> TokenStream2 {
             fn delete_by_id_sql() -> &'static str {
                 #model_schema_ident.delete_by_id_sql
             }
+
+            fn force_delete_by_id_sql() -> &'static str {
+                #model_schema_ident.force_delete_by_id_sql
+            }
+
+            fn select_with_deleted_sql() -> &'static str {
+                #model_schema_ident.select_with_deleted_sql
+            }
+
+            fn not_deleted_sql() -> Option<&'static str> {
+                #model_schema_ident.not_deleted_sql
+            }
+
+            fn select_only_deleted_sql() -> Option<&'static str> {
+                #model_schema_ident.select_only_deleted_sql
+            }
+
+            fn restore_by_id_sql() -> Option<&'static str> {
+                #model_schema_ident.restore_by_id_sql
+            }
+
+            fn purge_deleted_sql() -> Option<&'static str> {
+                #model_schema_ident.purge_deleted_sql
+            }
+
+            fn delete_as_by_id_sql() -> Option<&'static str> {
+                #model_schema_ident.delete_as_by_id_sql
+            }
+
+            fn soft_delete_sql() -> Option<&'static str> {
+                #model_schema_ident.soft_delete_sql
+            }
+
+            fn create_table_sql() -> &'static str {
+                #model_schema_ident.create_table_sql
+            }
+
+            fn drop_table_sql() -> &'static str {
+                #model_schema_ident.drop_table_sql
+            }
         }
 
+        #crud_hooks_impl
+
+        #validate_impl
+
         #[automatically_derived]
         impl<'e> #crate_name::traits::Crud<'e, &'e ::sqlx::pool::Pool<#db_ty>> for #ident {
-            fn insert_args(self) -> <#db_ty as ::sqlx::database::HasArguments<'e>>::Arguments {
+            fn insert_args(#insert_self) -> <#db_ty as ::sqlx::database::HasArguments<'e>>::Arguments {
                 use ::sqlx::Arguments as _;
+                #(#insert_timestamps)*
                 let mut args = <#db_ty as ::sqlx::database::HasArguments<'e>>::Arguments::default();
                 args.reserve(1usize, #(#insert_query_size)+*);
                 #(#insert_query_args)*
                 args
             }
 
-            fn update_args(self) -> <#db_ty as ::sqlx::database::HasArguments<'e>>::Arguments {
+            fn update_args(#update_self) -> <#db_ty as ::sqlx::database::HasArguments<'e>>::Arguments {
                 use ::sqlx::Arguments as _;
+                #(#update_timestamps)*
                 let mut args = <#db_ty as ::sqlx::database::HasArguments<'e>>::Arguments::default();
                 args.reserve(1usize, #(#update_query_size)+*);
                 #(#update_query_args)*
                 #update_query_args_id
                 args
             }
+
+            #execute_write
+
+            #publish_event
+        }
+    }
+}
+
+/// Overrides `execute_write` of `#[crud(audited)]` and `#[crud(outbox)]`
+/// models to record their changes in the audit log and the outbox, and of
+/// `#[auto_increment]` models to read back the primary key of creates.
+fn build_execute_write(config: &Config, id_ty: &Type) -> TokenStream2 {
+    let crate_name = &config.crate_name;
+    let db_ty = config.db_ty.sqlx_db();
+    let inserted_id = build_inserted_id(config, id_ty);
+    let signature = quote! {
+        fn execute_write(
+            pool: &'e ::sqlx::pool::Pool<#db_ty>,
+            operation: #crate_name::traits::Operation,
+            id: Self::Id,
+            query: ::sqlx::query::Query<'e, #db_ty, <#db_ty as ::sqlx::database::HasArguments<'e>>::Arguments>,
+        ) -> #crate_name::traits::CrudFut<'e, (<#db_ty as ::sqlx::Database>::QueryResult, Self::Id)>
+    };
+
+    if !config.audited && !config.outbox {
+        if !config.id_auto_increment {
+            return quote! {};
+        }
+        return quote! {
+            #signature {
+                ::std::boxed::Box::pin(#crate_name::write::run::<#db_ty, Self, _>(
+                    pool,
+                    operation,
+                    id,
+                    query,
+                    #inserted_id,
+                ))
+            }
+        };
+    }
+
+    let table_name = config.quote_ident(&config.table_name);
+    let id_column = config.quote_ident(&config.id_column);
+    let select_sql = format!(
+        "SELECT {} FROM {} WHERE {}.{} = ?",
+        build_column_list(config, &table_name),
+        table_name,
+        table_name,
+        id_column
+    );
+
+    quote! {
+        #signature {
+            ::std::boxed::Box::pin(#crate_name::write::execute::<#db_ty, Self>(
+                pool,
+                operation,
+                id,
+                query,
+                #select_sql,
+                #inserted_id,
+                |result| result.rows_affected(),
+            ))
+        }
+    }
+}
+
+/// Overrides `publish_event` of `#[crud(events)]` models to publish their
+/// changes to the events subscribers.
+fn build_publish_event(config: &Config) -> TokenStream2 {
+    if !config.events {
+        return quote! {};
+    }
+
+    let crate_name = &config.crate_name;
+    let db_ty = config.db_ty.sqlx_db();
+
+    quote! {
+        fn publish_event<'r>(
+            pool: &'e ::sqlx::pool::Pool<#db_ty>,
+            operation: #crate_name::traits::Operation,
+            id: Self::Id,
+            result: &'r <#db_ty as ::sqlx::Database>::QueryResult,
+        ) -> #crate_name::traits::CrudFut<'r, ()>
+        where
+            'e: 'r,
+        {
+            let changed = result.rows_affected() > 0;
+            ::std::boxed::Box::pin(async move {
+                if !changed {
+                    return ::std::result::Result::Ok(());
+                }
+                let fetch = <Self as #crate_name::traits::Crud<'e, &'e ::sqlx::pool::Pool<#db_ty>>>::by_id(pool, id);
+                #crate_name::events::publish::<Self, _>(operation, id, fetch).await;
+                ::std::result::Result::Ok(())
+            })
         }
     }
 }
 
+/// Returns a closure reading the primary key assigned by the database back
+/// from the query result on SQLite and MySQL. Postgres and MSSQL return it
+/// from the insert instead, see `build_insert_sql`.
+fn build_inserted_id(config: &Config, id_ty: &Type) -> TokenStream2 {
+    let last_insert_id = match config.db_ty {
+        DbType::Sqlite if config.id_auto_increment => quote! { result.last_insert_rowid() },
+        DbType::MySql if config.id_auto_increment => quote! { result.last_insert_id() },
+        _ => return quote! { |id, _| id },
+    };
+
+    quote! {
+        |id, result| <#id_ty as ::std::convert::TryFrom<_>>::try_from(#last_insert_id).unwrap_or(id)
+    }
+}
+
 #[allow(dead_code)] // Usage in quote macros aren't flagged as used
 struct Config<'a> {
+    vis: &'a Visibility,
     ident: &'a Ident,
     named: &'a Punctuated<Field, Comma>,
     crate_name: TokenStream2,
@@ -314,19 +1148,85 @@ struct Config<'a> {
     model_schema_ident: Ident,
     table_name: String,
     id_column_ident: Ident,
+    id_column: String,
+    id_auto_increment: bool,
     external_id: bool,
+    hooks: bool,
+    audited: bool,
+    outbox: bool,
+    events: bool,
+    has_many: Vec<HasMany>,
+    many_to_many: Vec<ManyToMany>,
     // additional fields
     update_fields: Vec<&'a Field>,
     insert_fields: Vec<&'a Field>,
     delete_field: Option<&'a Field>,
+    deleted_with: Option<DeletedWith>,
+    deleted_by_field: Option<&'a Field>,
+}
+
+/// How the `#[deleted_with]` field marks a row as deleted.
+enum DeletedWith {
+    /// `#[deleted_with(now)]` or `#[deleted_with = "now()"]`, the current
+    /// timestamp of the database.
+    Now,
+    /// `#[deleted_with(flag)]`, a `bool` field set to true.
+    Flag,
+    /// `#[deleted_with = "..."]`, an SQL expression used as is.
+    Expr(String),
+}
+
+impl DeletedWith {
+    fn new(field: &Field) -> Option<Self> {
+        let attr = field
+            .attrs
+            .iter()
+            .find(|attr| attr.path().is_ident("deleted_with"))?;
+        let deleted_with = match &attr.meta {
+            syn::Meta::NameValue(syn::MetaNameValue {
+                value:
+                    Expr::Lit(ExprLit {
+                        lit: syn::Lit::Str(lit_str),
+                        ..
+                    }),
+                ..
+            }) => match lit_str.value().as_str() {
+                "now()" => Self::Now,
+                expr => Self::Expr(expr.to_string()),
+            },
+            syn::Meta::List(_) => match attr.parse_args::<syn::Path>().expect(DELETED_WITH_USAGE) {
+                p if p.is_ident("now") => Self::Now,
+                p if p.is_ident("flag") => Self::Flag,
+                _ => panic!("{}", DELETED_WITH_USAGE),
+            },
+            _ => panic!("{}", DELETED_WITH_USAGE),
+        };
+
+        match deleted_with {
+            Self::Flag if type_ident(&field.ty).is_none_or(|i| i != "bool") => {
+                panic!("#[deleted_with(flag)] must be on a bool field")
+            }
+            Self::Now if !is_option(&field.ty) => {
+                panic!("#[deleted_with(now)] must be on an Option<T> field")
+            }
+            _ => Some(deleted_with),
+        }
+    }
 }
 
 impl<'a> Config<'a> {
-    fn new(attrs: &[Attribute], ident: &'a Ident, named: &'a Punctuated<Field, Comma>) -> Self {
+    fn new(
+        attrs: &[Attribute],
+        vis: &'a Visibility,
+        ident: &'a Ident,
+        named: &'a Punctuated<Field, Comma>,
+    ) -> Self {
         let crate_name = std::env::var("CARGO_PKG_NAME").unwrap();
         let is_doctest = std::env::vars()
             .any(|(k, _)| k == "UNSTABLE_RUSTDOC_TEST_LINE" || k == "UNSTABLE_RUSTDOC_TEST_PATH");
-        let crate_name = if !is_doctest && crate_name == "sqlx-crud" {
+        // Integration tests share the package name but are separate crates.
+        let is_library = std::env::var("CARGO_CRATE_NAME").map_or(true, |c| c == "sqlx_crud");
+        let crate_name = if !is_doctest && is_library && crate_name == "sqlx-crud" {
             quote! { crate }
         } else {
             quote! { ::sqlx_crud }
@@ -335,15 +1235,36 @@ impl<'a> Config<'a> {
         let delete_field = named.iter().find(|f| {
             f.attrs
                 .iter()
-                .find(|attr| attr.path().is_ident("deleted_with"))
-                .is_some()
+                .any(|attr| attr.path().is_ident("deleted_with"))
         });
+        let deleted_with = delete_field.and_then(DeletedWith::new);
+        let deleted_by_field = named.iter().find(|f| {
+            f.attrs
+                .iter()
+                .any(|attr| attr.path().is_ident("deleted_by"))
+        });
+        if deleted_by_field.is_some() && delete_field.is_none() {
+            panic!("#[deleted_by] requires a #[deleted_with] field");
+        }
         let db_ty = DbType::new(attrs);
 
         let model_schema_ident =
             format_ident!("{}_SCHEMA", ident.to_string().to_screaming_snake_case());
 
-        let table_name = ident.to_string().to_table_case();
+        // Use #[table_name = "..."] or default to the plural of the struct name
+        let table_name = attrs
+            .iter()
+            .find(|a| a.path().is_ident("table_name"))
+            .map(
+                |a| match &a.meta.require_name_value().expect(TABLE_NAME_USAGE).value {
+                    Expr::Lit(ExprLit {
+                        lit: syn::Lit::Str(lit_str),
+                        ..
+                    }) => lit_str.value(),
+                    _ => panic!("{}", TABLE_NAME_USAGE),
+                },
+            )
+            .unwrap_or_else(|| ident.to_string().to_table_case());
 
         // Search for a field with the #[id] attribute
         let id_field = named
@@ -357,13 +1278,41 @@ impl<'a> Config<'a> {
         // .and_then(|f| f.ident.as_ref());
         // Otherwise default to the first field as the "id" column
         let id_column_ident = id_field.clone().ident.unwrap().clone();
+        let id_column = column_name(id_field);
         let external_id = attrs.iter().any(|a| a.path().is_ident("external_id"));
+        let hooks = attrs.iter().any(|a| a.path().is_ident("hooks"));
+        let (mut audited, mut outbox, mut events) = (false, false, false);
+        for attr in attrs.iter().filter(|a| a.path().is_ident("crud")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("audited") {
+                    audited = true;
+                } else if meta.path.is_ident("outbox") {
+                    outbox = true;
+                } else if meta.path.is_ident("events") {
+                    events = true;
+                } else {
+                    return Err(meta.error(CRUD_USAGE));
+                }
+                Ok(())
+            })
+            .expect(CRUD_USAGE);
+        }
+
+        let has_many = attrs
+            .iter()
+            .filter(|a| a.path().is_ident("has_many"))
+            .map(|a| HasMany::new(a, ident))
+            .collect();
+        let many_to_many = attrs
+            .iter()
+            .filter(|a| a.path().is_ident("many_to_many"))
+            .map(|a| ManyToMany::new(a, ident))
+            .collect();
 
         let insert_fields = named
             .iter()
             .filter(|f| {
-                let is_not_id =
-                    f.ident.as_ref().unwrap().to_string() != id_column_ident.to_string();
+                let is_not_id = f.ident.as_ref() != Some(&id_column_ident);
                 let no_ignore_attr = !f.attrs.iter().any(|attr| Self::has_ignore(attr, "insert"));
                 if id_auto_increment {
                     is_not_id && no_ignore_attr
@@ -375,12 +1324,14 @@ impl<'a> Config<'a> {
         let update_fields = named
             .iter()
             .filter(|f| {
-                f.ident.as_ref().unwrap().to_string() != id_column_ident.to_string()
+                f.ident.as_ref() != Some(&id_column_ident)
+                    && !Self::is_created_at(f)
                     && !f.attrs.iter().any(|attr| Self::has_ignore(attr, "update"))
             })
             .collect();
 
         Self {
+            vis,
             ident,
             named,
             crate_name,
@@ -388,10 +1339,20 @@ impl<'a> Config<'a> {
             model_schema_ident,
             table_name,
             id_column_ident,
+            id_column,
+            id_auto_increment,
             external_id,
+            hooks,
+            audited,
+            outbox,
+            events,
+            has_many,
+            many_to_many,
             insert_fields,
             update_fields,
             delete_field,
+            deleted_with,
+            deleted_by_field,
         }
     }
 
@@ -399,9 +1360,67 @@ impl<'a> Config<'a> {
         self.db_ty.quote_ident(ident)
     }
 
-    fn delete_ident(&self) -> Option<String> {
-        self.delete_field
-            .map(|f| f.ident.clone().unwrap().to_string())
+    fn is_insertable(&self, field: &Field) -> bool {
+        self.insert_fields.iter().any(|f| f.ident == field.ident)
+    }
+
+    fn is_updatable(&self, field: &Field) -> bool {
+        self.update_fields.iter().any(|f| f.ident == field.ident)
+    }
+
+    fn is_soft_delete(&self, field: &Field) -> bool {
+        self.delete_field.is_some_and(|f| f.ident == field.ident)
+    }
+
+    /// The SQL value the `#[deleted_with]` field is set to on delete.
+    fn deleted_value(&self) -> Option<String> {
+        self.deleted_with.as_ref().map(|d| match d {
+            DeletedWith::Now => self.db_ty.current_timestamp().to_string(),
+            DeletedWith::Flag => self.db_ty.bool_literal(true).to_string(),
+            DeletedWith::Expr(expr) => expr.clone(),
+        })
+    }
+
+    /// The condition matching rows which are soft deleted.
+    fn deleted_sql(&self) -> Option<String> {
+        let field = self.quote_ident(&column_name(self.delete_field?));
+        self.deleted_with.as_ref().map(|d| match d {
+            DeletedWith::Flag => format!("{} = {}", field, self.db_ty.bool_literal(true)),
+            _ => format!("{} IS NOT NULL", field),
+        })
+    }
+
+    /// The condition matching rows which aren't soft deleted.
+    fn not_deleted_sql(&self) -> Option<String> {
+        let field = self.quote_ident(&column_name(self.delete_field?));
+        self.deleted_with.as_ref().map(|d| match d {
+            DeletedWith::Flag => format!("{} = {}", field, self.db_ty.bool_literal(false)),
+            _ => format!("{} IS NULL", field),
+        })
+    }
+
+    /// Returns `Some(unique)` for fields tagged with `#[index]` or
+    /// `#[index(unique)]`.
+    fn index(field: &Field) -> Option<bool> {
+        field
+            .attrs
+            .iter()
+            .find(|attr| attr.path().is_ident("index"))
+            .map(|attr| match &attr.meta {
+                syn::Meta::Path(_) => false,
+                _ => attr
+                    .parse_args::<syn::Path>()
+                    .expect("index must be like #[index] or #[index(unique)]")
+                    .is_ident("unique"),
+            })
+    }
+
+    fn is_created_at(field: &Field) -> bool {
+        field.attrs.iter().any(|a| a.path().is_ident("created_at"))
+    }
+
+    fn is_updated_at(field: &Field) -> bool {
+        field.attrs.iter().any(|a| a.path().is_ident("updated_at"))
     }
 
     fn has_ignore(attr: &Attribute, target: &str) -> bool {
@@ -437,9 +1456,9 @@ impl From<&str> for DbType {
         }
     }
 }
-impl Into<DbType> for String {
-    fn into(self) -> DbType {
-        DbType::from(self.as_str())
+impl From<String> for DbType {
+    fn from(db_type: String) -> Self {
+        DbType::from(db_type.as_str())
     }
 }
 
@@ -473,6 +1492,105 @@ impl DbType {
         }
     }
 
+    fn dialect(&self, crate_name: &TokenStream2) -> TokenStream2 {
+        match self {
+            Self::Any => quote! { #crate_name::schema::Dialect::Any },
+            Self::Mssql => quote! { #crate_name::schema::Dialect::Mssql },
+            Self::MySql => quote! { #crate_name::schema::Dialect::MySql },
+            Self::Postgres => quote! { #crate_name::schema::Dialect::Postgres },
+            Self::Sqlite => quote! { #crate_name::schema::Dialect::Sqlite },
+        }
+    }
+
+    /// Maps a Rust field type to the column type used by the generated DDL.
+    /// `Option<T>` maps to the column type of `T`. Unknown types fall back to
+    /// a text column.
+    fn sql_type(&self, ty: &Type) -> String {
+        let ty = strip_option(ty);
+        let ident = type_ident(ty).map(|i| i.to_string()).unwrap_or_default();
+        let sql_type = match (self, ident.as_str()) {
+            (_, "bool") => match self {
+                Self::Mssql => "BIT",
+                _ => "BOOLEAN",
+            },
+            (Self::Sqlite | Self::Any, "i8" | "i16" | "i32" | "i64" | "u8" | "u16" | "u32") => {
+                "INTEGER"
+            }
+            (Self::MySql, "i8") => "TINYINT",
+            (Self::MySql, "u8") => "TINYINT UNSIGNED",
+            (Self::MySql, "u16") => "SMALLINT UNSIGNED",
+            (Self::MySql, "u32") => "INT UNSIGNED",
+            (Self::MySql, "u64") => "BIGINT UNSIGNED",
+            (Self::MySql | Self::Mssql, "i32") => "INT",
+            (Self::Mssql, "i8" | "u8") => "TINYINT",
+            (_, "i8" | "i16" | "u8") => "SMALLINT",
+            (_, "i32" | "u16") => "INTEGER",
+            (_, "i64" | "u32" | "u64") => "BIGINT",
+            (Self::MySql, "f32") => "FLOAT",
+            (Self::MySql, "f64") => "DOUBLE",
+            (Self::Postgres | Self::Mssql, "f64") => "DOUBLE PRECISION",
+            (_, "f32" | "f64") => "REAL",
+            (Self::MySql, "String" | "str") => "VARCHAR(255)",
+            (Self::Mssql, "String" | "str") => "NVARCHAR(MAX)",
+            (Self::Postgres, "Vec") if is_bytes(ty) => "BYTEA",
+            (Self::Mssql, "Vec") if is_bytes(ty) => "VARBINARY(MAX)",
+            (_, "Vec") if is_bytes(ty) => "BLOB",
+            (Self::Postgres, "Uuid") => "UUID",
+            (Self::MySql, "Uuid") => "BINARY(16)",
+            (Self::Mssql, "Uuid") => "UNIQUEIDENTIFIER",
+            (Self::Sqlite | Self::Any, "Uuid") => "BLOB",
+            (Self::Sqlite | Self::Any, "DateTime" | "OffsetDateTime") => "DATETIME",
+            (Self::Sqlite | Self::Any, "NaiveDateTime" | "PrimitiveDateTime") => "DATETIME",
+            (Self::Postgres, "DateTime" | "OffsetDateTime") => "TIMESTAMPTZ",
+            (Self::MySql, "DateTime" | "OffsetDateTime") => "TIMESTAMP",
+            (Self::Mssql, "DateTime" | "OffsetDateTime") => "DATETIMEOFFSET",
+            (Self::MySql, "NaiveDateTime" | "PrimitiveDateTime") => "DATETIME",
+            (Self::Mssql, "NaiveDateTime" | "PrimitiveDateTime") => "DATETIME2",
+            (_, "NaiveDateTime" | "PrimitiveDateTime") => "TIMESTAMP",
+            (_, "NaiveDate" | "Date") => "DATE",
+            (_, "NaiveTime" | "Time") => "TIME",
+            (Self::Sqlite | Self::Any, "Decimal" | "BigDecimal") => "NUMERIC",
+            (_, "Decimal" | "BigDecimal") => "DECIMAL",
+            (Self::Postgres, "Json" | "JsonValue" | "Value") => "JSONB",
+            (Self::MySql, "Json" | "JsonValue" | "Value") => "JSON",
+            _ => "TEXT",
+        };
+
+        sql_type.to_string()
+    }
+
+    /// Column type and constraints of an `#[auto_increment]` primary key.
+    fn auto_increment_column(&self, ty: &Type) -> String {
+        match self {
+            Self::Any | Self::Sqlite => "INTEGER PRIMARY KEY AUTOINCREMENT".to_string(),
+            Self::MySql => format!("{} NOT NULL AUTO_INCREMENT PRIMARY KEY", self.sql_type(ty)),
+            Self::Mssql => format!("{} NOT NULL IDENTITY(1, 1) PRIMARY KEY", self.sql_type(ty)),
+            Self::Postgres => match self.sql_type(ty).as_str() {
+                "SMALLINT" => "SMALLSERIAL PRIMARY KEY".to_string(),
+                "INTEGER" => "SERIAL PRIMARY KEY".to_string(),
+                _ => "BIGSERIAL PRIMARY KEY".to_string(),
+            },
+        }
+    }
+
+    /// The current timestamp, used for `#[deleted_with(now)]` and the
+    /// default of database filled timestamp columns.
+    fn current_timestamp(&self) -> &'static str {
+        match self {
+            Self::Mssql => "SYSDATETIME()",
+            _ => "CURRENT_TIMESTAMP",
+        }
+    }
+
+    fn bool_literal(&self, value: bool) -> &'static str {
+        match (self, value) {
+            (Self::Mssql, true) => "1",
+            (Self::Mssql, false) => "0",
+            (_, true) => "TRUE",
+            (_, false) => "FALSE",
+        }
+    }
+
     fn quote_ident(&self, ident: &str) -> String {
         match self {
             Self::Any => format!(r#""{}""#, &ident),
@@ -484,6 +1602,13 @@ impl DbType {
     }
 }
 
+const DELETED_WITH_USAGE: &str =
+    "deleted_with must be like #[deleted_with(now)], #[deleted_with(flag)] or #[deleted_with = \"...\"]";
+const VALIDATE_USAGE: &str =
+    "validate must be like #[validate(length(min = 1, max = 255), range(min = 0), regex = \"...\")]";
+const CRUD_USAGE: &str = "crud must be like #[crud(audited, outbox, events)]";
+const TABLE_NAME_USAGE: &str = "table_name must be a string like #[table_name = \"users\"]";
+
 #[cfg(feature = "default_mysql")]
 static DEFAULT_DB_TYPE: Option<&str> = Some("mysql");
 #[cfg(not(any(feature = "default_mysql")))]
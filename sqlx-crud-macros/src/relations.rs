@@ -0,0 +1,354 @@
+use inflector::Inflector;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{Attribute, Field, Ident, LitStr, Path, Type};
+
+use crate::{is_option, Config};
+
+/// Arguments of the `#[has_many(Child, foreign_key = "...", name = "...")]`
+/// struct attribute.
+pub(crate) struct HasMany {
+    child: Path,
+    foreign_key: String,
+    name: String,
+}
+
+impl HasMany {
+    pub(crate) fn new(attr: &Attribute, ident: &Ident) -> Self {
+        let (mut child, mut foreign_key, mut name) = (None, None, None);
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("foreign_key") {
+                foreign_key = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("name") {
+                name = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if child.is_none() {
+                child = Some(meta.path);
+            } else {
+                return Err(meta.error(HAS_MANY_USAGE));
+            }
+            Ok(())
+        })
+        .expect(HAS_MANY_USAGE);
+        let child: Path = child.expect(HAS_MANY_USAGE);
+
+        let name = name.unwrap_or_else(|| {
+            let child = child.segments.last().unwrap().ident.to_string();
+            child.to_snake_case().to_plural()
+        });
+        let foreign_key =
+            foreign_key.unwrap_or_else(|| format!("{}_id", ident.to_string().to_snake_case()));
+
+        Self {
+            child,
+            foreign_key,
+            name,
+        }
+    }
+}
+
+/// Arguments of the `#[many_to_many(Remote, through = "...", local = "...",
+/// remote = "...")]` struct attribute.
+pub(crate) struct ManyToMany {
+    remote: Path,
+    through: String,
+    local_column: String,
+    remote_column: String,
+}
+
+impl ManyToMany {
+    pub(crate) fn new(attr: &Attribute, ident: &Ident) -> Self {
+        let (mut remote, mut through, mut local_column, mut remote_column) =
+            (None, None, None, None);
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("through") {
+                through = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("local") {
+                local_column = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("remote") {
+                remote_column = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if remote.is_none() {
+                remote = Some(meta.path);
+            } else {
+                return Err(meta.error(MANY_TO_MANY_USAGE));
+            }
+            Ok(())
+        })
+        .expect(MANY_TO_MANY_USAGE);
+        let remote: Path = remote.expect(MANY_TO_MANY_USAGE);
+
+        let local_name = ident.to_string().to_snake_case();
+        let remote_name = remote
+            .segments
+            .last()
+            .unwrap()
+            .ident
+            .to_string()
+            .to_snake_case();
+        let through =
+            through.unwrap_or_else(|| format!("{}_{}", local_name, remote_name.to_plural()));
+        let local_column = local_column.unwrap_or_else(|| format!("{}_id", local_name));
+        let remote_column = remote_column.unwrap_or_else(|| format!("{}_id", remote_name));
+
+        Self {
+            remote,
+            through,
+            local_column,
+            remote_column,
+        }
+    }
+}
+
+/// Builds the inherent methods of the relationships of the model.
+pub(crate) fn build_relations(config: &Config) -> TokenStream2 {
+    let ident = config.ident;
+    let methods = config
+        .named
+        .iter()
+        .filter_map(|f| build_belongs_to(config, f))
+        .chain(config.has_many.iter().map(|h| build_has_many(config, h)))
+        .chain(config.many_to_many.iter().map(|m| build_many_to_many(config, m)))
+        .collect::<Vec<_>>();
+    if methods.is_empty() {
+        return quote! {};
+    }
+
+    quote! {
+        #[automatically_derived]
+        impl #ident {
+            #(#methods)*
+        }
+    }
+}
+
+/// `#[belongs_to(Parent)]` generates a method loading the parent of a record
+/// and one loading the parents of many records with a single query.
+fn build_belongs_to(config: &Config, field: &Field) -> Option<TokenStream2> {
+    let attr = field
+        .attrs
+        .iter()
+        .find(|a| a.path().is_ident("belongs_to"))?;
+    let parent = attr.parse_args::<Path>().expect(BELONGS_TO_USAGE);
+    let crate_name = &config.crate_name;
+    let db_ty = config.db_ty.sqlx_db();
+    let field_ident = field.ident.as_ref().unwrap();
+
+    let name = match field_ident.to_string().strip_suffix("_id") {
+        Some(name) => name.to_string(),
+        None => parent.segments.last().unwrap().ident.to_string().to_snake_case(),
+    };
+    let one = format_ident!("{}", name);
+    let many = format_ident!("load_{}", name.to_plural());
+    let one_doc = format!("Looks up the record referenced by `{}`.", field_ident);
+    let many_doc = format!(
+        "Loads the records referenced by `{}` of `records` with a single query. \
+         The ids are deduplicated, more than 1000 are split over several queries.",
+        field_ident
+    );
+
+    let parent_crud =
+        quote! { <#parent as #crate_name::traits::Crud<'e, &'e ::sqlx::pool::Pool<#db_ty>>> };
+    let (by_id, ids) = if is_option(&field.ty) {
+        (
+            quote! {
+                match &self.#field_ident {
+                    ::std::option::Option::Some(id) => #parent_crud::by_id(pool, id.clone()),
+                    ::std::option::Option::None => ::std::boxed::Box::pin(async {
+                        ::std::result::Result::Ok(::std::option::Option::None)
+                    }),
+                }
+            },
+            quote! { records.iter().filter_map(|r| r.#field_ident.clone()).collect() },
+        )
+    } else {
+        (
+            quote! { #parent_crud::by_id(pool, self.#field_ident.clone()) },
+            quote! { records.iter().map(|r| r.#field_ident.clone()).collect() },
+        )
+    };
+
+    Some(quote! {
+        #[doc = #one_doc]
+        pub fn #one<'e>(
+            &self,
+            pool: &'e ::sqlx::pool::Pool<#db_ty>,
+        ) -> #crate_name::traits::CrudFut<'e, ::std::option::Option<#parent>> {
+            #by_id
+        }
+
+        #[doc = #many_doc]
+        pub fn #many<'e>(
+            pool: &'e ::sqlx::pool::Pool<#db_ty>,
+            records: &[Self],
+        ) -> #crate_name::traits::CrudFut<'e, ::std::vec::Vec<#parent>> {
+            let ids: ::std::vec::Vec<_> = #ids;
+            ::std::boxed::Box::pin(#crate_name::relations::select_in::<#db_ty, #parent, _>(
+                pool,
+                <#parent as #crate_name::traits::Schema>::id_column(),
+                ids,
+            ))
+        }
+    })
+}
+
+/// `#[has_many(Child)]` generates a method loading the children of a record
+/// and one pairing many records with their children with a single query.
+fn build_has_many(config: &Config, has_many: &HasMany) -> TokenStream2 {
+    let HasMany {
+        child,
+        foreign_key,
+        name,
+    } = has_many;
+    let crate_name = &config.crate_name;
+    let db_ty = config.db_ty.sqlx_db();
+    let id = &config.id_column_ident;
+    let id_ty = id_type(config);
+    let foreign_key_ident = format_ident!("{}", foreign_key);
+
+    let one = format_ident!("{}", name);
+    let with = format_ident!("with_{}", name);
+    let one_doc = format!("Loads the {} of the record.", name);
+    let with_doc = format!(
+        "Pairs every record of `records` with its {}, loaded with a single query. \
+         The ids are deduplicated, more than 1000 are split over several queries.",
+        name
+    );
+
+    quote! {
+        #[doc = #one_doc]
+        pub fn #one<'e>(
+            &self,
+            pool: &'e ::sqlx::pool::Pool<#db_ty>,
+        ) -> #crate_name::traits::CrudFut<'e, ::std::vec::Vec<#child>> {
+            ::std::boxed::Box::pin(#crate_name::relations::select_in::<#db_ty, #child, _>(
+                pool,
+                #foreign_key,
+                ::std::vec![self.#id.clone()],
+            ))
+        }
+
+        #[doc = #with_doc]
+        pub fn #with<'e>(
+            pool: &'e ::sqlx::pool::Pool<#db_ty>,
+            records: ::std::vec::Vec<Self>,
+        ) -> #crate_name::traits::CrudFut<'e, ::std::vec::Vec<(Self, ::std::vec::Vec<#child>)>> {
+            ::std::boxed::Box::pin(async move {
+                let ids = records.iter().map(|r| r.#id.clone()).collect();
+                let mut children =
+                    ::std::collections::HashMap::<#id_ty, ::std::vec::Vec<#child>>::new();
+                for row in #crate_name::relations::select_in::<#db_ty, #child, _>(pool, #foreign_key, ids).await? {
+                    let key = #crate_name::relations::ForeignKey::<#id_ty>::key(&row.#foreign_key_ident);
+                    if let ::std::option::Option::Some(key) = key {
+                        children.entry(key).or_default().push(row);
+                    }
+                }
+
+                ::std::result::Result::Ok(
+                    records
+                        .into_iter()
+                        .map(|record| {
+                            let rows = children.remove(&record.#id).unwrap_or_default();
+                            (record, rows)
+                        })
+                        .collect(),
+                )
+            })
+        }
+    }
+}
+
+/// `#[many_to_many(Remote)]` generates methods loading the related records
+/// and maintaining the rows of the join table.
+fn build_many_to_many(config: &Config, many_to_many: &ManyToMany) -> TokenStream2 {
+    let ManyToMany {
+        remote,
+        through,
+        local_column,
+        remote_column,
+    } = many_to_many;
+    let crate_name = &config.crate_name;
+    let db_ty = config.db_ty.sqlx_db();
+    let id = &config.id_column_ident;
+
+    let name = remote
+        .segments
+        .last()
+        .unwrap()
+        .ident
+        .to_string()
+        .to_snake_case();
+    let all = format_ident!("{}", name.to_plural());
+    let attach = format_ident!("attach_{}", name);
+    let detach = format_ident!("detach_{}", name);
+    let sync = format_ident!("sync_{}", name.to_plural());
+    let all_doc = format!("Loads the {} related to the record through `{}`.", all, through);
+    let attach_doc = format!("Relates the {} `{}` to the record.", name, remote_column);
+    let detach_doc = format!("Removes the {} `{}` from the record.", name, remote_column);
+    let sync_doc = format!(
+        "Relates exactly the {} of `{}` to the record in a transaction.",
+        all,
+        remote_column.to_plural()
+    );
+
+    let remote_arg = format_ident!("{}", remote_column);
+    let remote_args = format_ident!("{}", remote_column.to_plural());
+    let remote_id = quote! { <#remote as #crate_name::traits::Schema>::Id };
+    let join_table = quote! {
+        #crate_name::relations::JoinTable {
+            dialect: <Self as #crate_name::traits::Schema>::dialect(),
+            table: #through,
+            local: #local_column,
+            remote: #remote_column,
+        }
+    };
+
+    quote! {
+        #[doc = #all_doc]
+        pub fn #all<'e>(
+            &self,
+            pool: &'e ::sqlx::pool::Pool<#db_ty>,
+        ) -> #crate_name::traits::CrudFut<'e, ::std::vec::Vec<#remote>> {
+            ::std::boxed::Box::pin(#join_table.select::<#db_ty, #remote, _>(pool, self.#id))
+        }
+
+        #[doc = #attach_doc]
+        pub fn #attach<'e>(
+            &self,
+            pool: &'e ::sqlx::pool::Pool<#db_ty>,
+            #remote_arg: #remote_id,
+        ) -> #crate_name::traits::CrudFut<'e, ()> {
+            ::std::boxed::Box::pin(#join_table.attach::<#db_ty, _, _>(pool, self.#id, #remote_arg))
+        }
+
+        #[doc = #detach_doc]
+        pub fn #detach<'e>(
+            &self,
+            pool: &'e ::sqlx::pool::Pool<#db_ty>,
+            #remote_arg: #remote_id,
+        ) -> #crate_name::traits::CrudFut<'e, ()> {
+            ::std::boxed::Box::pin(#join_table.detach::<#db_ty, _, _>(pool, self.#id, #remote_arg))
+        }
+
+        #[doc = #sync_doc]
+        pub fn #sync<'e>(
+            &self,
+            pool: &'e ::sqlx::pool::Pool<#db_ty>,
+            #remote_args: &'e [#remote_id],
+        ) -> #crate_name::traits::CrudFut<'e, ()> {
+            ::std::boxed::Box::pin(#join_table.sync::<#db_ty, _, _>(pool, self.#id, #remote_args))
+        }
+    }
+}
+
+fn id_type<'a>(config: &'a Config) -> &'a Type {
+    config
+        .named
+        .iter()
+        .find(|f| f.ident.as_ref() == Some(&config.id_column_ident))
+        .map(|f| &f.ty)
+        .expect("the id type")
+}
+
+const BELONGS_TO_USAGE: &str = "belongs_to must be a model like #[belongs_to(User)]";
+const MANY_TO_MANY_USAGE: &str = "many_to_many must be like #[many_to_many(Role, through = \"user_roles\", local = \"user_id\", remote = \"role_id\")]";
+const HAS_MANY_USAGE: &str =
+    "has_many must be like #[has_many(Post, foreign_key = \"user_id\", name = \"posts\")]";
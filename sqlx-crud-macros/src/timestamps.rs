@@ -0,0 +1,89 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::meta::ParseNestedMeta;
+use syn::parse::Parser;
+use syn::{Data, DataStruct, DeriveInput, Field, Fields, FieldsNamed, Ident, Lit, Type};
+
+/// Arguments of the `#[timestamps(...)]` attribute. A `None` name leaves the
+/// field out.
+pub(crate) struct Timestamps {
+    created_at: Option<Ident>,
+    updated_at: Option<Ident>,
+    deleted_at: Option<Ident>,
+    ty: Type,
+}
+
+impl Default for Timestamps {
+    fn default() -> Self {
+        Self {
+            created_at: Some(format_ident!("created_at")),
+            updated_at: Some(format_ident!("updated_at")),
+            deleted_at: Some(format_ident!("deleted_at")),
+            ty: syn::parse_quote! {
+                ::sqlx::types::chrono::DateTime<::sqlx::types::chrono::Local>
+            },
+        }
+    }
+}
+
+impl Timestamps {
+    pub(crate) fn parse(&mut self, meta: ParseNestedMeta) -> syn::Result<()> {
+        let field = if meta.path.is_ident("created_at") {
+            &mut self.created_at
+        } else if meta.path.is_ident("updated_at") {
+            &mut self.updated_at
+        } else if meta.path.is_ident("deleted_at") {
+            &mut self.deleted_at
+        } else if meta.path.is_ident("ty") {
+            self.ty = match meta.value()?.parse()? {
+                Lit::Str(ty) => ty.parse()?,
+                lit => return Err(syn::Error::new_spanned(lit, TIMESTAMPS_USAGE)),
+            };
+            return Ok(());
+        } else {
+            return Err(meta.error(TIMESTAMPS_USAGE));
+        };
+
+        *field = match meta.value()?.parse()? {
+            Lit::Str(name) => Some(name.parse()?),
+            Lit::Bool(b) if !b.value => None,
+            lit => return Err(syn::Error::new_spanned(lit, TIMESTAMPS_USAGE)),
+        };
+        Ok(())
+    }
+
+    /// Appends the timestamp fields to the struct `input`.
+    pub(crate) fn expand(&self, mut input: DeriveInput) -> TokenStream2 {
+        let Data::Struct(DataStruct {
+            fields: Fields::Named(FieldsNamed { named, .. }),
+            ..
+        }) = &mut input.data
+        else {
+            panic!("#[timestamps] only works on structs with named fields");
+        };
+
+        let ty = &self.ty;
+        let fields = [
+            self.created_at.as_ref().map(|name| {
+                quote! { #[created_at] pub #name: Option<#ty> }
+            }),
+            self.updated_at.as_ref().map(|name| {
+                quote! { #[updated_at] pub #name: Option<#ty> }
+            }),
+            self.deleted_at.as_ref().map(|name| {
+                quote! {
+                    #[ignore_when(insert, update)]
+                    #[deleted_with(now)]
+                    pub #name: Option<#ty>
+                }
+            }),
+        ];
+        for field in fields.into_iter().flatten() {
+            named.push(Field::parse_named.parse2(field).unwrap());
+        }
+
+        quote! { #input }
+    }
+}
+
+const TIMESTAMPS_USAGE: &str = "timestamps must be like #[timestamps(created_at = \"...\", updated_at = \"...\", deleted_at = false, ty = \"...\")]";
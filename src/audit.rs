@@ -0,0 +1,251 @@
+//! Audit log of the changes written by the [Crud](crate::Crud) methods.
+//!
+//! Models tagged with `#[crud(audited)]` record every
+//! [create](crate::Crud::create), [update](crate::Crud::update) and
+//! [delete](crate::Crud::delete) in the `audit_log` table. The entry is
+//! written in the same transaction as the change, so either both are
+//! committed or neither is. An entry holds the table name, the primary key,
+//! the [Operation], the row before and after the change as JSON, the actor
+//! and the UTC time of the change.
+//!
+//! Audited models have to implement `serde::Serialize` and their id type
+//! `Display`. The old and new values are the rows as read from the database
+//! inside the transaction, so columns filled in by the database or by
+//! `#[updated_at]` are included. The new values of a hard deleted row are
+//! `None`. The primary key assigned to a created `#[auto_increment]` row is
+//! read back with `RETURNING` on Postgres and `OUTPUT INSERTED` on MSSQL.
+//! Writes changing no row, such as an update of a soft deleted record, aren't
+//! recorded.
+//!
+//! The actor is set with [with_actor] for the writes done by a future. The
+//! table is created with [create_table_sql] and [history] returns the entries
+//! of a record.
+//!
+//! # Example
+//!
+//! ```rust
+//! use serde::Serialize;
+//! use sqlx::{FromRow, SqlitePool};
+//! use sqlx_crud::audit;
+//! use sqlx_crud::{Crud, Operation, Schema, SqlxCrud};
+//!
+//! #[derive(FromRow, Serialize, SqlxCrud)]
+//! #[crud(audited)]
+//! struct Invoice {
+//!     #[id]
+//!     #[auto_increment]
+//!     invoice_id: i64,
+//!     amount: i64,
+//! }
+//!
+//! # tokio_test::block_on(async {
+//! let pool = SqlitePool::connect(":memory:").await?;
+//! sqlx::query(Invoice::create_table_sql()).execute(&pool).await?;
+//! sqlx::query(&audit::create_table_sql(Invoice::dialect())).execute(&pool).await?;
+//!
+//! audit::with_actor("alice", async {
+//!     Invoice { invoice_id: 0, amount: 100 }.create(&pool).await?;
+//!     let mut invoice = Invoice::by_id(&pool, 1).await?.unwrap();
+//!     invoice.amount = 150;
+//!     invoice.update(&pool).await?;
+//!     Invoice::by_id(&pool, 1).await?.unwrap().delete(&pool).await
+//! })
+//! .await?;
+//!
+//! let history = audit::history::<Invoice, _>(&pool, 1).await?;
+//! assert_eq!(
+//!     vec![Operation::Create, Operation::Update, Operation::Delete],
+//!     history.iter().map(|e| e.operation).collect::<Vec<_>>()
+//! );
+//! assert_eq!(Some(r#"{"invoice_id":1,"amount":100}"#), history[1].old_values.as_deref());
+//! assert_eq!(Some(r#"{"invoice_id":1,"amount":150}"#), history[1].new_values.as_deref());
+//! assert_eq!(None, history[2].new_values);
+//! assert_eq!(Some("alice"), history[2].actor.as_deref());
+//! # Ok::<(), sqlx::Error>(())
+//! # }).unwrap();
+//! ```
+
+use std::cell::RefCell;
+use std::fmt;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::SystemTime;
+
+use futures::Future;
+use sqlx::database::HasArguments;
+use sqlx::{ColumnIndex, Database, Decode, Encode, Executor, IntoArguments, Pool, Row, Type};
+
+use crate::schema::{Dialect, LogColumn};
+use crate::traits::{Operation, Schema};
+
+/// Name of the audit log table.
+pub const TABLE_NAME: &str = "audit_log";
+
+/// A row of the audit log returned by [history].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuditEntry {
+    pub audit_id: i64,
+    pub table_name: String,
+    /// The primary key of the changed record formatted with `Display`.
+    pub record_id: String,
+    pub operation: Operation,
+    /// The row before the change as JSON. `None` for creates.
+    pub old_values: Option<String>,
+    /// The row after the change as JSON. `None` when the row was removed.
+    pub new_values: Option<String>,
+    /// The actor set with [with_actor], if any.
+    pub actor: Option<String>,
+    /// UTC time of the change formatted as `YYYY-MM-DD HH:MM:SS`.
+    pub created_at: String,
+}
+
+/// Returns the statement creating the audit log table for `dialect`.
+pub fn create_table_sql(dialect: Dialect) -> String {
+    dialect.create_log_table_sql(
+        TABLE_NAME,
+        "audit_id",
+        &[
+            ("table_name", LogColumn::Text),
+            ("record_id", LogColumn::Text),
+            ("operation", LogColumn::Text),
+            ("old_values", LogColumn::OptionalDocument),
+            ("new_values", LogColumn::OptionalDocument),
+            ("actor", LogColumn::OptionalText),
+            ("created_at", LogColumn::Text),
+        ],
+    )
+}
+
+/// Returns the audit log entries of the record of `T` with the primary key
+/// `id`, oldest first.
+pub async fn history<T, DB>(pool: &Pool<DB>, id: T::Id) -> Result<Vec<AuditEntry>, sqlx::Error>
+where
+    T: Schema,
+    T::Id: fmt::Display,
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> <DB as HasArguments<'q>>::Arguments: IntoArguments<'q, DB>,
+    for<'q> String: Encode<'q, DB> + Type<DB>,
+    for<'r> String: Decode<'r, DB> + Type<DB>,
+    for<'r> i64: Decode<'r, DB> + Type<DB>,
+    usize: ColumnIndex<DB::Row>,
+{
+    let dialect = T::dialect();
+    let q = |ident| dialect.quote_ident(ident);
+    let sql = format!(
+        "SELECT {}, {}, {}, {}, {}, {}, {}, {} FROM {} WHERE {} = ? AND {} = ? ORDER BY {}",
+        q("audit_id"),
+        q("table_name"),
+        q("record_id"),
+        q("operation"),
+        q("old_values"),
+        q("new_values"),
+        q("actor"),
+        q("created_at"),
+        q(TABLE_NAME),
+        q("table_name"),
+        q("record_id"),
+        q("audit_id"),
+    );
+
+    sqlx::query::<DB>(&sql)
+        .bind(T::table_name().to_string())
+        .bind(id.to_string())
+        .try_map(|row: DB::Row| {
+            Ok(AuditEntry {
+                audit_id: row.try_get(0)?,
+                table_name: row.try_get(1)?,
+                record_id: row.try_get(2)?,
+                operation: row
+                    .try_get::<String, _>(3)?
+                    .parse()
+                    .map_err(|e: String| sqlx::Error::Decode(e.into()))?,
+                old_values: row.try_get(4)?,
+                new_values: row.try_get(5)?,
+                actor: row.try_get(6)?,
+                created_at: row.try_get(7)?,
+            })
+        })
+        .fetch_all(pool)
+        .await
+}
+
+thread_local! {
+    static ACTOR: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Runs `future` with `actor` recorded as the actor of its audited writes.
+/// The actor is only visible to the future itself, not to tasks it spawns.
+pub fn with_actor<F: Future>(actor: impl Into<String>, future: F) -> WithActor<F> {
+    WithActor {
+        actor: Some(actor.into()),
+        future: Box::pin(future),
+    }
+}
+
+/// Returns the actor set with [with_actor] for the current future.
+pub fn actor() -> Option<String> {
+    ACTOR.with(|a| a.borrow().clone())
+}
+
+/// Future returned by [with_actor].
+pub struct WithActor<F> {
+    actor: Option<String>,
+    future: Pin<Box<F>>,
+}
+
+impl<F: Future> Future for WithActor<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        let outer = ACTOR.with(|a| a.replace(this.actor.take()));
+        let poll = this.future.as_mut().poll(cx);
+        this.actor = ACTOR.with(|a| a.replace(outer));
+        poll
+    }
+}
+
+/// Inserts an audit log entry of the record `record_id` of `T`.
+pub(crate) async fn insert<DB, T>(
+    conn: &mut DB::Connection,
+    record_id: String,
+    operation: Operation,
+    old_values: Option<String>,
+    new_values: Option<String>,
+) -> Result<(), sqlx::Error>
+where
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> <DB as HasArguments<'q>>::Arguments: IntoArguments<'q, DB>,
+    for<'q> String: Encode<'q, DB> + Type<DB>,
+    for<'q> Option<String>: Encode<'q, DB>,
+    T: Schema,
+{
+    let dialect = T::dialect();
+    let q = |ident| dialect.quote_ident(ident);
+    let sql = format!(
+        "INSERT INTO {} ({}, {}, {}, {}, {}, {}, {}) VALUES (?, ?, ?, ?, ?, ?, ?)",
+        q(TABLE_NAME),
+        q("table_name"),
+        q("record_id"),
+        q("operation"),
+        q("old_values"),
+        q("new_values"),
+        q("actor"),
+        q("created_at"),
+    );
+
+    sqlx::query::<DB>(&sql)
+        .bind(T::table_name().to_string())
+        .bind(record_id)
+        .bind(operation.as_str().to_string())
+        .bind(old_values)
+        .bind(new_values)
+        .bind(actor())
+        .bind(crate::timestamp::format_utc(SystemTime::now()))
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
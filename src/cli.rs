@@ -0,0 +1,257 @@
+//! The `sqlx-crud` command line interface.
+//!
+//! ```text
+//! sqlx-crud migrate <name> [--source <dir>] [--database-url <url>] [--dialect <name>]
+//! sqlx-crud generate [<file.db>] [--database-url <url>] [--table <name>]... [--output <file>]
+//! ```
+//!
+//! `migrate` writes a migration bringing the database up to date with the
+//! registered models, see [migrate](crate::migrate). The database is
+//! compared against the live schema when a database URL is given with
+//! `--database-url` or `DATABASE_URL`, otherwise against the snapshot saved
+//! in the migrations directory by the previous run. Only the models of the
+//! database's dialect are migrated. Without a database the dialect is the one
+//! of the registered models, or selected with `--dialect` (`sqlite`,
+//! `postgres`, `mysql` or `mssql`) when they use several.
+//!
+//! `generate` emits a model struct for each table of an existing database,
+//! see [codegen](crate::codegen). A SQLite database can be given as a file
+//! path instead of a URL. All tables are generated unless some are selected
+//! with `--table`, and the source is printed unless `--output` is given.
+//!
+//! Models are only known to binaries they are linked in to, so this crate
+//! doesn't ship a `sqlx-crud` binary, it would have no models to migrate.
+//! Add a binary calling [main] to the application instead:
+//!
+//! ```rust,ignore
+//! // src/bin/sqlx-crud.rs
+//! fn main() {
+//!     sqlx_crud::cli::main()
+//! }
+//! ```
+//!
+//! This module requires the `cli` feature.
+
+use std::path::PathBuf;
+
+use sqlx::any::{AnyKind, AnyPool};
+
+use crate::codegen;
+use crate::introspect::Introspect;
+use crate::migrate::{self, Snapshot};
+use crate::registry::{self, Model};
+use crate::schema::Dialect;
+
+const USAGE: &str =
+    "usage: sqlx-crud migrate <name> [--source <dir>] [--database-url <url>] [--dialect <name>]
+       sqlx-crud generate [<file.db>] [--database-url <url>] [--table <name>]... [--output <file>]
+
+migrate plans the models linked in to this binary, run it from a binary of
+your application calling sqlx_crud::cli::main().";
+
+/// Errors returned by [run].
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("{0}\n{USAGE}")]
+    Usage(String),
+    #[error("table {0} does not exist")]
+    UnknownTable(String),
+    #[error(
+        "no models are linked in to this binary, add a binary calling sqlx_crud::cli::main() \
+         to your application and run migrate with it"
+    )]
+    NoModels,
+    #[error("no models use the {0:?} dialect")]
+    NoModelsFor(Dialect),
+    #[error(transparent)]
+    Plan(#[from] migrate::PlanError),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Sqlx(#[from] sqlx::Error),
+}
+
+/// Runs the command line interface with the process arguments and exits
+/// with a non zero status on error.
+pub fn main() {
+    let result = tokio::runtime::Runtime::new()
+        .map_err(Error::from)
+        .and_then(|runtime| runtime.block_on(run(std::env::args().skip(1))));
+
+    if let Err(e) = result {
+        eprintln!("error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+/// Runs the command line interface with `args`, not including the program
+/// name.
+pub async fn run(args: impl IntoIterator<Item = String>) -> Result<(), Error> {
+    let mut args = args.into_iter();
+    let command = args.next();
+    let mut positional = Vec::new();
+    let mut source = PathBuf::from("migrations");
+    let mut database_url = std::env::var("DATABASE_URL").ok();
+    let mut tables = Vec::new();
+    let mut output = None;
+    let mut dialect_name = None;
+
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| Error::Usage(format!("missing value for {}", arg)))
+        };
+        match arg.as_str() {
+            "--source" => source = PathBuf::from(value()?),
+            "--database-url" => database_url = Some(value()?),
+            "--table" => tables.push(value()?),
+            "--output" => output = Some(PathBuf::from(value()?)),
+            "--dialect" => dialect_name = Some(value()?),
+            _ if arg.starts_with("--") => {
+                return Err(Error::Usage(format!("unknown option {}", arg)))
+            }
+            _ => positional.push(arg),
+        }
+    }
+
+    match (command.as_deref(), positional.as_slice()) {
+        (Some("migrate"), [name]) => {
+            let dialect = dialect_name.as_deref().map(parse_dialect).transpose()?;
+            migrate(name, source, database_url, dialect).await
+        }
+        (Some("migrate"), _) => Err(Error::Usage("expected a migration name".to_string())),
+        (Some("generate"), [file]) => {
+            let url = format!("sqlite://{}", file);
+            generate(&url, tables, output).await
+        }
+        (Some("generate"), []) => match database_url {
+            Some(url) => generate(&url, tables, output).await,
+            None => Err(Error::Usage("expected a database url".to_string())),
+        },
+        (Some("generate"), _) => Err(Error::Usage("expected a single database".to_string())),
+        (Some(command), _) => Err(Error::Usage(format!("unknown command {}", command))),
+        (None, _) => Err(Error::Usage("expected a command".to_string())),
+    }
+}
+
+async fn migrate(
+    name: &str,
+    source: PathBuf,
+    database_url: Option<String>,
+    dialect_arg: Option<Dialect>,
+) -> Result<(), Error> {
+    if registry::models().is_empty() {
+        return Err(Error::NoModels);
+    }
+
+    let (models, current) = match database_url {
+        Some(url) => {
+            let pool = AnyPool::connect(&url).await?;
+            let models = models_for(dialect(pool.any_kind()))?;
+            let current = Snapshot::from_database(&pool, &models).await?;
+            (models, current)
+        }
+        None => {
+            let dialect = match dialect_arg {
+                Some(dialect) => dialect,
+                None => registered_dialect()?,
+            };
+            (models_for(dialect)?, Snapshot::load(&source)?)
+        }
+    };
+
+    let statements = migrate::plan(&models, &current)?;
+    if statements.is_empty() {
+        println!("database is up to date with {} models", models.len());
+        return Ok(());
+    }
+
+    let path = migrate::write_migration(&source, name, &statements)?;
+    Snapshot::from_models(&models).save(&source)?;
+    println!("wrote {}", path.display());
+
+    Ok(())
+}
+
+async fn generate(url: &str, tables: Vec<String>, output: Option<PathBuf>) -> Result<(), Error> {
+    let pool = AnyPool::connect(url).await?;
+    let dialect = dialect(pool.any_kind());
+
+    let table_names = if tables.is_empty() {
+        Introspect::table_names(&pool, dialect)
+            .await?
+            .into_iter()
+            .filter(|t| t != "_sqlx_migrations")
+            .collect()
+    } else {
+        tables
+    };
+    let mut tables = Vec::new();
+    for table_name in table_names {
+        let columns = Introspect::table_columns(&pool, dialect, &table_name).await?;
+        if columns.is_empty() {
+            return Err(Error::UnknownTable(table_name));
+        }
+        tables.push((table_name, columns));
+    }
+
+    let source = codegen::generate(dialect, &tables);
+    match output {
+        Some(path) => {
+            std::fs::write(&path, source)?;
+            println!("wrote {}", path.display());
+        }
+        None => print!("{}", source),
+    }
+
+    Ok(())
+}
+
+fn models_for(dialect: Dialect) -> Result<Vec<&'static Model>, Error> {
+    let models = registry::models()
+        .into_iter()
+        .filter(|m| m.dialect == dialect || m.dialect == Dialect::Any)
+        .collect::<Vec<_>>();
+    if models.is_empty() {
+        return Err(Error::NoModelsFor(dialect));
+    }
+    Ok(models)
+}
+
+/// Returns the dialect of the registered models, which must all use the same
+/// one apart from `Any`.
+fn registered_dialect() -> Result<Dialect, Error> {
+    let mut dialects = Vec::new();
+    for model in registry::models() {
+        if model.dialect != Dialect::Any && !dialects.contains(&model.dialect) {
+            dialects.push(model.dialect);
+        }
+    }
+    match dialects.as_slice() {
+        [] => Ok(Dialect::Any),
+        [dialect] => Ok(*dialect),
+        _ => Err(Error::Usage(
+            "the models use several dialects, select one with --dialect".to_string(),
+        )),
+    }
+}
+
+fn parse_dialect(name: &str) -> Result<Dialect, Error> {
+    match name {
+        "mssql" => Ok(Dialect::Mssql),
+        "mysql" => Ok(Dialect::MySql),
+        "postgres" => Ok(Dialect::Postgres),
+        "sqlite" => Ok(Dialect::Sqlite),
+        _ => Err(Error::Usage(format!("unknown dialect {}", name))),
+    }
+}
+
+fn dialect(kind: AnyKind) -> Dialect {
+    #[allow(unreachable_patterns)]
+    match kind {
+        AnyKind::MySql => Dialect::MySql,
+        AnyKind::Postgres => Dialect::Postgres,
+        AnyKind::Sqlite => Dialect::Sqlite,
+        _ => Dialect::Mssql,
+    }
+}
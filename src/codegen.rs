@@ -0,0 +1,239 @@
+//! Generates model structs from existing tables.
+//!
+//! This is the reverse of [create_table_sql](crate::Schema::create_table_sql):
+//! given the columns reported by [Introspect](crate::introspect::Introspect)
+//! it emits a struct deriving `FromRow` and `SqlxCrud` for each table.
+//! Column types are mapped to the Rust types sqlx decodes them as, nullable
+//! columns become `Option<T>`, the primary key is tagged with `#[id]` and
+//! `#[auto_increment]` when the database assigns it, and other columns with
+//! a default are tagged with `#[ignore_when(insert)]`.
+//!
+//! Columns named after a Rust keyword become raw identifiers like `r#type`.
+//! Other names which aren't identifiers, e.g. containing spaces or dashes,
+//! become snake case fields tagged with `#[sqlx(rename = "...")]`.
+//!
+//! Date, time, decimal, UUID and JSON columns map to the types re-exported
+//! under `sqlx::types` and need the matching sqlx feature enabled.
+
+use inflector::Inflector;
+
+use crate::introspect::TableColumn;
+use crate::schema::Dialect;
+
+/// Returns the source of a module defining a struct for each of `tables`.
+pub fn generate(dialect: Dialect, tables: &[(String, Vec<TableColumn>)]) -> String {
+    let mut source = String::from("use sqlx::FromRow;\nuse sqlx_crud::SqlxCrud;\n");
+    for (table_name, columns) in tables {
+        source.push('\n');
+        source.push_str(&model_source(dialect, table_name, columns));
+    }
+    source
+}
+
+/// Returns the source of a struct for the table `table_name`.
+///
+/// # Example
+///
+/// ```rust
+/// use sqlx_crud::codegen;
+/// use sqlx_crud::introspect::TableColumn;
+/// use sqlx_crud::schema::Dialect;
+///
+/// let column = |name: &str, data_type: &str, nullable, has_default, primary_key| TableColumn {
+///     name: name.to_string(),
+///     data_type: data_type.to_string(),
+///     nullable,
+///     has_default,
+///     primary_key,
+/// };
+/// let columns = vec![
+///     column("id", "INTEGER", false, false, true),
+///     column("task", "TEXT", false, false, false),
+///     column("done", "BOOLEAN", true, true, false),
+/// ];
+///
+/// assert_eq!(
+///     r#"#[derive(Debug, FromRow, SqlxCrud)]
+/// #[database(sqlite)]
+/// pub struct Task {
+///     #[id]
+///     #[auto_increment]
+///     pub id: i64,
+///     pub task: String,
+///     #[ignore_when(insert)]
+///     pub done: Option<bool>,
+/// }
+/// "#,
+///     codegen::model_source(Dialect::Sqlite, "tasks", &columns)
+/// );
+/// ```
+pub fn model_source(dialect: Dialect, table_name: &str, columns: &[TableColumn]) -> String {
+    let struct_name = table_name.to_class_case();
+    let primary_keys = columns.iter().filter(|c| c.primary_key).count();
+
+    let mut source = String::from("#[derive(Debug, FromRow, SqlxCrud)]\n");
+    source.push_str(&format!("#[database({})]\n", database(dialect)));
+    if struct_name.to_table_case() != table_name {
+        source.push_str(&format!("#[table_name = \"{}\"]\n", table_name));
+    }
+    source.push_str(&format!("pub struct {} {{\n", struct_name));
+    match primary_keys {
+        0 => source.push_str("    // No primary key, the first column is used as the id.\n"),
+        1 => {}
+        _ => source
+            .push_str("    // Composite primary key, only the first column is used as the id.\n"),
+    }
+
+    let mut id_assigned = false;
+    for column in columns {
+        let rust_type = rust_type(dialect, &column.data_type);
+        if column.primary_key && !id_assigned {
+            id_assigned = true;
+            source.push_str("    #[id]\n");
+            if is_auto_increment(dialect, column, rust_type) {
+                source.push_str("    #[auto_increment]\n");
+            }
+        } else if column.has_default {
+            source.push_str("    #[ignore_when(insert)]\n");
+        }
+
+        let field_type = if column.nullable && !column.primary_key {
+            format!("Option<{}>", rust_type)
+        } else {
+            rust_type.to_string()
+        };
+        let (field, rename) = field_name(&column.name);
+        if rename {
+            source.push_str(&format!("    #[sqlx(rename = {:?})]\n", column.name));
+        }
+        source.push_str(&format!("    pub {}: {},\n", field, field_type));
+    }
+    source.push_str("}\n");
+
+    source
+}
+
+/// Rust keywords, which are only field names as raw identifiers.
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "if", "impl", "in",
+    "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "Self", "static", "struct", "super", "trait", "true", "try", "type",
+    "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// Returns the field name of the column `name` and whether it needs to be
+/// renamed to the column with `#[sqlx(rename)]`.
+fn field_name(name: &str) -> (String, bool) {
+    let is_identifier = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && name != "_";
+    if is_identifier && !KEYWORDS.contains(&name) {
+        return (name.to_string(), false);
+    }
+    // These keywords can't be raw identifiers.
+    if is_identifier && !matches!(name, "crate" | "self" | "Self" | "super") {
+        return (format!("r#{}", name), false);
+    }
+
+    let sanitized = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect::<String>()
+        .to_snake_case();
+    let field = match sanitized.as_str() {
+        "" => "column".to_string(),
+        s if s.starts_with(|c: char| c.is_ascii_digit()) => format!("_{}", s),
+        s if KEYWORDS.contains(&s) => format!("{}_", s),
+        s => s.to_string(),
+    };
+    (field, true)
+}
+
+fn database(dialect: Dialect) -> &'static str {
+    match dialect {
+        Dialect::Any => "any",
+        Dialect::Mssql => "mssql",
+        Dialect::MySql => "mysql",
+        Dialect::Postgres => "postgres",
+        Dialect::Sqlite => "sqlite",
+    }
+}
+
+fn is_auto_increment(dialect: Dialect, column: &TableColumn, rust_type: &str) -> bool {
+    let integer = rust_type.starts_with('i') || rust_type.starts_with('u');
+    match dialect {
+        // INTEGER PRIMARY KEY columns alias the rowid, which SQLite assigns.
+        Dialect::Sqlite | Dialect::Any => column.data_type.eq_ignore_ascii_case("integer"),
+        _ => integer && column.has_default,
+    }
+}
+
+/// Maps a column type as reported by the database catalog to a Rust type.
+/// Unknown types map to `String`.
+fn rust_type(dialect: Dialect, data_type: &str) -> &'static str {
+    let data_type = data_type.to_lowercase();
+    let unsigned = data_type.contains("unsigned");
+    let base = data_type.split(['(', ' ']).next().unwrap_or_default();
+
+    match dialect {
+        // Column affinity rules, see https://www.sqlite.org/datatype3.html
+        Dialect::Sqlite | Dialect::Any => match data_type.as_str() {
+            t if t.starts_with("bool") => "bool",
+            t if t.contains("int") || t.contains("serial") => "i64",
+            t if t.contains("char") || t.contains("clob") || t.contains("text") => "String",
+            t if t.contains("blob") || t.is_empty() => "Vec<u8>",
+            t if t.contains("real") || t.contains("floa") || t.contains("doub") => "f64",
+            t if t.starts_with("numeric") || t.starts_with("decimal") => "f64",
+            "date" => "sqlx::types::chrono::NaiveDate",
+            "time" => "sqlx::types::chrono::NaiveTime",
+            t if t.starts_with("datetime") || t.starts_with("timestamp") => {
+                "sqlx::types::chrono::NaiveDateTime"
+            }
+            _ => "String",
+        },
+        Dialect::MySql => match (base, unsigned) {
+            ("tinyint", _) if data_type.starts_with("tinyint(1)") => "bool",
+            ("bool" | "boolean" | "bit", _) => "bool",
+            ("tinyint", false) => "i8",
+            ("tinyint", true) => "u8",
+            ("smallint", false) => "i16",
+            ("smallint", true) => "u16",
+            ("mediumint" | "int" | "integer", false) => "i32",
+            ("mediumint" | "int" | "integer", true) => "u32",
+            ("bigint", false) => "i64",
+            ("bigint", true) => "u64",
+            ("float", _) => "f32",
+            ("double" | "real", _) => "f64",
+            ("decimal" | "numeric", _) => "sqlx::types::Decimal",
+            ("binary" | "varbinary" | "tinyblob" | "blob" | "mediumblob" | "longblob", _) => {
+                "Vec<u8>"
+            }
+            ("date", _) => "sqlx::types::chrono::NaiveDate",
+            ("time", _) => "sqlx::types::chrono::NaiveTime",
+            ("datetime", _) => "sqlx::types::chrono::NaiveDateTime",
+            ("timestamp", _) => "sqlx::types::chrono::DateTime<sqlx::types::chrono::Utc>",
+            ("json", _) => "sqlx::types::JsonValue",
+            _ => "String",
+        },
+        Dialect::Postgres | Dialect::Mssql => match data_type.as_str() {
+            "boolean" | "bit" => "bool",
+            "smallint" => "i16",
+            "integer" | "int" => "i32",
+            "bigint" => "i64",
+            "real" => "f32",
+            "double precision" | "float" => "f64",
+            "numeric" | "decimal" => "sqlx::types::Decimal",
+            "bytea" | "varbinary" => "Vec<u8>",
+            "uuid" | "uniqueidentifier" => "sqlx::types::Uuid",
+            "date" => "sqlx::types::chrono::NaiveDate",
+            "time without time zone" | "time" => "sqlx::types::chrono::NaiveTime",
+            "timestamp without time zone" | "datetime2" => "sqlx::types::chrono::NaiveDateTime",
+            "timestamp with time zone" | "datetimeoffset" => {
+                "sqlx::types::chrono::DateTime<sqlx::types::chrono::Utc>"
+            }
+            "json" | "jsonb" => "sqlx::types::JsonValue",
+            _ => "String",
+        },
+    }
+}
@@ -0,0 +1,156 @@
+//! In-process notifications of the changes written by the
+//! [Crud](crate::Crud) methods.
+//!
+//! Models tagged with `#[crud(events)]` publish a [ModelEvent] after every
+//! successful [create](crate::Crud::create), [update](crate::Crud::update)
+//! and [delete](crate::Crud::delete) that changed a row, once the change is
+//! committed. Writes matching no row, e.g. updates of a missing record, are
+//! not published. Events are sent on a tokio [broadcast] channel per model
+//! type, so [subscribe] only receives the events of the model it was called
+//! for. A subscriber falling more than 1024 events behind misses the oldest
+//! ones and is told so by
+//! [RecvError::Lagged](broadcast::error::RecvError::Lagged).
+//!
+//! The row of an event is read back from the database after the write, and
+//! only when the model has subscribers. It is `None` for deletes and when
+//! the row couldn't be read. Events models have to be `Sync` and `'static`.
+//!
+//! # Example
+//!
+//! ```rust
+//! use sqlx::{FromRow, SqlitePool};
+//! use sqlx_crud::{events, Crud, Operation, Schema, SqlxCrud};
+//!
+//! #[derive(FromRow, SqlxCrud)]
+//! #[crud(events)]
+//! struct Message {
+//!     #[id]
+//!     #[auto_increment]
+//!     message_id: i64,
+//!     body: String,
+//! }
+//!
+//! # tokio_test::block_on(async {
+//! let pool = SqlitePool::connect(":memory:").await?;
+//! sqlx::query(Message::create_table_sql()).execute(&pool).await?;
+//!
+//! let mut messages = events::subscribe::<Message>();
+//! Message { message_id: 0, body: "hello".to_string() }.create(&pool).await?;
+//! Message::by_id(&pool, 1).await?.unwrap().delete(&pool).await?;
+//!
+//! let created = messages.recv().await.unwrap();
+//! assert_eq!((Operation::Create, 1), (created.op, created.id));
+//! assert_eq!("hello", created.row.unwrap().body);
+//!
+//! let deleted = messages.recv().await.unwrap();
+//! assert_eq!((Operation::Delete, 1), (deleted.op, deleted.id));
+//! assert!(deleted.row.is_none());
+//! # Ok::<(), sqlx::Error>(())
+//! # }).unwrap();
+//! ```
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use tokio::sync::broadcast;
+
+use crate::traits::{Operation, Schema};
+
+/// Number of events a subscriber can fall behind before missing events.
+const CAPACITY: usize = 1024;
+
+/// A change of a record of `T`.
+pub struct ModelEvent<T: Schema> {
+    pub op: Operation,
+    /// The primary key of the changed record.
+    pub id: T::Id,
+    /// The record after the change.
+    pub row: Option<Arc<T>>,
+}
+
+impl<T: Schema> Clone for ModelEvent<T> {
+    fn clone(&self) -> Self {
+        Self {
+            op: self.op,
+            id: self.id,
+            row: self.row.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for ModelEvent<T>
+where
+    T: Schema + fmt::Debug,
+    T::Id: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ModelEvent")
+            .field("op", &self.op)
+            .field("id", &self.id)
+            .field("row", &self.row)
+            .finish()
+    }
+}
+
+/// The broadcast senders of every model type, each a
+/// `broadcast::Sender<ModelEvent<T>>`.
+fn channels() -> &'static Mutex<HashMap<TypeId, Box<dyn Any + Send + Sync>>> {
+    static CHANNELS: OnceLock<Mutex<HashMap<TypeId, Box<dyn Any + Send + Sync>>>> =
+        OnceLock::new();
+    CHANNELS.get_or_init(Default::default)
+}
+
+/// Returns a receiver of the events of the model `T`.
+pub fn subscribe<T>() -> broadcast::Receiver<ModelEvent<T>>
+where
+    T: Schema + Send + Sync + 'static,
+    T::Id: 'static,
+{
+    channels()
+        .lock()
+        .unwrap()
+        .entry(TypeId::of::<T>())
+        .or_insert_with(|| Box::new(broadcast::channel::<ModelEvent<T>>(CAPACITY).0))
+        .downcast_ref::<broadcast::Sender<ModelEvent<T>>>()
+        .unwrap()
+        .subscribe()
+}
+
+/// Returns the sender of the events of `T` if it has any subscribers.
+fn sender<T>() -> Option<broadcast::Sender<ModelEvent<T>>>
+where
+    T: Schema + Send + Sync + 'static,
+    T::Id: 'static,
+{
+    channels()
+        .lock()
+        .unwrap()
+        .get(&TypeId::of::<T>())?
+        .downcast_ref::<broadcast::Sender<ModelEvent<T>>>()
+        .filter(|sender| sender.receiver_count() > 0)
+        .cloned()
+}
+
+/// Publishes an event of the record `id` of `T` to its subscribers.
+/// `fetch` reads the changed row and is only called when there are
+/// subscribers.
+#[doc(hidden)]
+pub async fn publish<T, F>(op: Operation, id: T::Id, fetch: F)
+where
+    T: Schema + Send + Sync + 'static,
+    T::Id: 'static,
+    F: futures::Future<Output = Result<Option<T>, sqlx::Error>>,
+{
+    let Some(sender) = sender::<T>() else {
+        return;
+    };
+
+    let row = match op {
+        Operation::Delete => None,
+        _ => fetch.await.ok().flatten().map(Arc::new),
+    };
+    // Fails only if every subscriber was dropped in the meantime.
+    let _ = sender.send(ModelEvent { op, id, row });
+}
@@ -0,0 +1,488 @@
+//! Conditions on the columns of a model.
+//!
+//! A [Filter] is built from the typed columns returned by the generated
+//! `Model::cols()`, e.g. `Task::cols().done.eq(false)`, and combined with
+//! [and](Filter::and) and [or](Filter::or). Values are bound as query
+//! parameters, never written into the SQL, and cloned every time the filter
+//! is run, so a filter can be cloned and run several times. Values have to
+//! be `'static`, like the field types of a model. Filters are taken by
+//! methods like [Crud::count_where](crate::Crud::count_where), which also
+//! skip soft deleted records.
+//!
+//! # Example
+//!
+//! ```rust
+//! use sqlx::{FromRow, SqlitePool};
+//! use sqlx_crud::{Crud, Schema, SqlxCrud};
+//!
+//! #[derive(FromRow, SqlxCrud)]
+//! struct Task {
+//!     task_id: i64,
+//!     done: bool,
+//!     estimate: Option<i64>,
+//! }
+//!
+//! # tokio_test::block_on(async {
+//! let pool = SqlitePool::connect(":memory:").await?;
+//! sqlx::query(Task::create_table_sql()).execute(&pool).await?;
+//!
+//! for (task_id, done, estimate) in [(1, true, Some(3)), (2, false, Some(5)), (3, false, None)] {
+//!     Task { task_id, done, estimate }.create(&pool).await?;
+//! }
+//!
+//! let cols = Task::cols();
+//! let open = cols.done.eq(false);
+//! assert_eq!(r#""tasks"."done" = ?"#, open.sql());
+//!
+//! assert_eq!(2, Task::count_where(&pool, open).await?);
+//! let filter = cols.estimate.gt(Some(4)).or(cols.estimate.is_null());
+//! assert_eq!(2, Task::count_where(&pool, filter).await?);
+//! assert_eq!(1, Task::count_where(&pool, cols.task_id.is_in(vec![1, 4])).await?);
+//! # Ok::<(), sqlx::Error>(())
+//! # }).unwrap();
+//! ```
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use sqlx::database::HasArguments;
+use sqlx::{Arguments, Database, Encode, Type};
+
+use crate::schema::Dialect;
+use crate::select::Column;
+use crate::traits::Schema;
+
+type Bind<'q, DB> =
+    Arc<dyn for<'a> Fn(&mut <DB as HasArguments<'a>>::Arguments) + Send + Sync + 'q>;
+
+/// A condition on the records of `T` for the database `DB`.
+pub struct Filter<'q, T, DB: Database> {
+    sql: String,
+    binds: Vec<Bind<'q, DB>>,
+    allow_all: bool,
+    model: PhantomData<fn() -> T>,
+}
+
+impl<'q, T, DB: Database> Filter<'q, T, DB> {
+    /// Matches every record. [Crud::update_where](crate::Crud::update_where)
+    /// and [Crud::delete_where](crate::Crud::delete_where) refuse it unless
+    /// [allow_all](Filter::allow_all) is called.
+    pub fn all() -> Self {
+        filter(String::new(), Vec::new())
+    }
+
+    /// Lets a filter matching every record, e.g. [all](Filter::all), update
+    /// or delete the whole table.
+    pub fn allow_all(mut self) -> Self {
+        self.allow_all = true;
+        self
+    }
+
+    /// Returns the SQL of the condition, with a `?` for every bound value,
+    /// or an empty string if it matches every record.
+    pub fn sql(&self) -> &str {
+        &self.sql
+    }
+
+    /// Matches the records matching both `self` and `other`.
+    pub fn and(self, other: Self) -> Self {
+        self.combine("AND", other)
+    }
+
+    /// Matches the records matching `self`, `other` or both.
+    pub fn or(self, other: Self) -> Self {
+        self.combine("OR", other)
+    }
+
+    /// Returns the arguments binding the values of the condition.
+    pub(crate) fn arguments<'a>(&self) -> <DB as HasArguments<'a>>::Arguments {
+        let mut args = <DB as HasArguments<'a>>::Arguments::default();
+        self.bind(&mut args);
+        args
+    }
+
+    /// Binds the values of the condition to `args`.
+    pub(crate) fn bind(&self, args: &mut <DB as HasArguments<'_>>::Arguments) {
+        for bind in &self.binds {
+            bind(args);
+        }
+    }
+
+    fn combine(mut self, operator: &str, other: Self) -> Self {
+        let allow_all = self.allow_all || other.allow_all;
+        // An empty filter matches every record.
+        let mut combined = match (self.sql.is_empty(), other.sql.is_empty(), operator) {
+            (true, _, "AND") | (false, true, "OR") => other,
+            (_, true, _) | (true, false, _) => self,
+            _ => {
+                self.sql = format!("({}) {} ({})", self.sql, operator, other.sql);
+                self.binds.extend(other.binds);
+                self
+            }
+        };
+        combined.allow_all = allow_all;
+        combined
+    }
+}
+
+impl<'q, T, DB: Database> Clone for Filter<'q, T, DB> {
+    fn clone(&self) -> Self {
+        Filter {
+            allow_all: self.allow_all,
+            ..filter(self.sql.clone(), self.binds.clone())
+        }
+    }
+}
+
+impl<'q, T, DB: Database> fmt::Debug for Filter<'q, T, DB> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Filter")
+            .field("sql", &self.sql)
+            .field("binds", &self.binds.len())
+            .finish()
+    }
+}
+
+impl<T: Schema, V> Column<T, V> {
+    /// Matches the records whose column equals `value`.
+    pub fn eq<'q, DB>(self, value: V) -> Filter<'q, T, DB>
+    where
+        DB: Database,
+        V: 'static + Clone + Send + Sync + for<'a> Encode<'a, DB> + Type<DB>,
+    {
+        self.compare("=", value)
+    }
+
+    /// Matches the records whose column doesn't equal `value`.
+    pub fn ne<'q, DB>(self, value: V) -> Filter<'q, T, DB>
+    where
+        DB: Database,
+        V: 'static + Clone + Send + Sync + for<'a> Encode<'a, DB> + Type<DB>,
+    {
+        self.compare("<>", value)
+    }
+
+    /// Matches the records whose column is less than `value`.
+    pub fn lt<'q, DB>(self, value: V) -> Filter<'q, T, DB>
+    where
+        DB: Database,
+        V: 'static + Clone + Send + Sync + for<'a> Encode<'a, DB> + Type<DB>,
+    {
+        self.compare("<", value)
+    }
+
+    /// Matches the records whose column is at most `value`.
+    pub fn le<'q, DB>(self, value: V) -> Filter<'q, T, DB>
+    where
+        DB: Database,
+        V: 'static + Clone + Send + Sync + for<'a> Encode<'a, DB> + Type<DB>,
+    {
+        self.compare("<=", value)
+    }
+
+    /// Matches the records whose column is greater than `value`.
+    pub fn gt<'q, DB>(self, value: V) -> Filter<'q, T, DB>
+    where
+        DB: Database,
+        V: 'static + Clone + Send + Sync + for<'a> Encode<'a, DB> + Type<DB>,
+    {
+        self.compare(">", value)
+    }
+
+    /// Matches the records whose column is at least `value`.
+    pub fn ge<'q, DB>(self, value: V) -> Filter<'q, T, DB>
+    where
+        DB: Database,
+        V: 'static + Clone + Send + Sync + for<'a> Encode<'a, DB> + Type<DB>,
+    {
+        self.compare(">=", value)
+    }
+
+    /// Matches the records whose column is one of `values`. Matches nothing
+    /// if `values` is empty.
+    pub fn is_in<'q, DB>(self, values: Vec<V>) -> Filter<'q, T, DB>
+    where
+        DB: Database,
+        V: 'static + Clone + Send + Sync + for<'a> Encode<'a, DB> + Type<DB>,
+    {
+        if values.is_empty() {
+            return filter("1 = 0".to_string(), Vec::new());
+        }
+
+        let sql = format!(
+            "{} IN ({})",
+            self.qualified(),
+            vec!["?"; values.len()].join(", ")
+        );
+        let binds = values.into_iter().map(|value| bind(value)).collect();
+        filter(sql, binds)
+    }
+
+    /// Matches the records whose column is NULL.
+    pub fn is_null<'q, DB: Database>(self) -> Filter<'q, T, DB> {
+        filter(format!("{} IS NULL", self.qualified()), Vec::new())
+    }
+
+    /// Matches the records whose column isn't NULL.
+    pub fn is_not_null<'q, DB: Database>(self) -> Filter<'q, T, DB> {
+        filter(format!("{} IS NOT NULL", self.qualified()), Vec::new())
+    }
+
+    /// Returns the column qualified with the table name.
+    pub(crate) fn qualified(&self) -> String {
+        let dialect = T::dialect();
+        format!(
+            "{}.{}",
+            dialect.quote_ident(T::table_name()),
+            dialect.quote_ident(self.name())
+        )
+    }
+
+    fn compare<'q, DB>(self, operator: &str, value: V) -> Filter<'q, T, DB>
+    where
+        DB: Database,
+        V: 'static + Clone + Send + Sync + for<'a> Encode<'a, DB> + Type<DB>,
+    {
+        filter(
+            format!("{} {} ?", self.qualified(), operator),
+            vec![bind(value)],
+        )
+    }
+}
+
+fn filter<'q, T, DB: Database>(sql: String, binds: Vec<Bind<'q, DB>>) -> Filter<'q, T, DB> {
+    Filter {
+        sql,
+        binds,
+        allow_all: false,
+        model: PhantomData,
+    }
+}
+
+/// The new values of columns of `T`, written to the records matching a
+/// filter by [Crud::update_where](crate::Crud::update_where).
+pub struct Patch<'q, T, DB: Database> {
+    sets: Vec<String>,
+    binds: Vec<Bind<'q, DB>>,
+    model: PhantomData<fn() -> T>,
+}
+
+impl<'q, T: Schema, DB: Database> Patch<'q, T, DB> {
+    /// Returns a patch changing no columns.
+    pub fn new() -> Self {
+        Self {
+            sets: Vec::new(),
+            binds: Vec::new(),
+            model: PhantomData,
+        }
+    }
+
+    /// Sets `column` to `value`.
+    pub fn set<V>(mut self, column: Column<T, V>, value: V) -> Self
+    where
+        V: 'static + Clone + Send + Sync + for<'a> Encode<'a, DB> + Type<DB>,
+    {
+        self.sets
+            .push(format!("{} = ?", T::dialect().quote_ident(column.name())));
+        self.binds.push(bind(value));
+        self
+    }
+
+    /// Returns the arguments binding the values of the patch.
+    pub(crate) fn arguments<'a>(&self) -> <DB as HasArguments<'a>>::Arguments {
+        let mut args = <DB as HasArguments<'a>>::Arguments::default();
+        for bind in &self.binds {
+            bind(&mut args);
+        }
+        args
+    }
+}
+
+impl<'q, T: Schema, DB: Database> Default for Patch<'q, T, DB> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'q, T, DB: Database> fmt::Debug for Patch<'q, T, DB> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Patch")
+            .field("sets", &self.sets)
+            .field("binds", &self.binds.len())
+            .finish()
+    }
+}
+
+/// Returns a bind adding a clone of `value` to the arguments it is given.
+fn bind<'q, DB, V>(value: V) -> Bind<'q, DB>
+where
+    DB: Database,
+    V: 'static + Clone + Send + Sync + for<'a> Encode<'a, DB> + Type<DB>,
+{
+    Arc::new(move |args: &mut <DB as HasArguments<'_>>::Arguments| args.add(value.clone()))
+}
+
+/// Returns the condition selecting the records of `T` matching `condition`,
+/// skipping soft deleted records, as a WHERE clause or an empty string.
+pub(crate) fn where_clause<T: Schema>(condition: Option<&str>) -> String {
+    let condition = condition.filter(|condition| !condition.is_empty());
+    match (T::not_deleted_sql(), condition) {
+        (None, None) => String::new(),
+        (Some(not_deleted), None) => format!(" WHERE {}", not_deleted),
+        (None, Some(condition)) => format!(" WHERE {}", condition),
+        (Some(not_deleted), Some(condition)) => {
+            format!(" WHERE {} AND ({})", not_deleted, condition)
+        }
+    }
+}
+
+/// Returns the statement applying the aggregate `function` to `column` over
+/// the records of `T` which aren't soft deleted.
+pub(crate) fn aggregate_sql<T: Schema, V>(function: &str, column: Column<T, V>) -> String {
+    let dialect = T::dialect();
+    let mut expr = format!("{}({})", function, column.qualified());
+    // Postgres and MySQL widen the SUM of integers, cast it back to the
+    // column type so it decodes as `V`. MySQL only casts to SIGNED or
+    // UNSIGNED, which are BIGINTs, and sqlx decodes narrower integers from
+    // them, failing if the sum doesn't fit.
+    let sql_type = T::column_meta()
+        .iter()
+        .find(|c| c.name == column.name())
+        .map_or("", |c| c.sql_type);
+    if function == "SUM" && sql_type.contains("INT") {
+        match dialect {
+            Dialect::Postgres => expr = format!("CAST({} AS {})", expr, sql_type),
+            Dialect::MySql if sql_type.contains("UNSIGNED") => {
+                expr = format!("CAST({} AS UNSIGNED)", expr)
+            }
+            Dialect::MySql => expr = format!("CAST({} AS SIGNED)", expr),
+            _ => {}
+        }
+    }
+
+    format!(
+        "SELECT {} FROM {}{}",
+        expr,
+        dialect.quote_ident(T::table_name()),
+        where_clause::<T>(None)
+    )
+}
+
+/// Returns the statement adding a value to `column` of the record of `T`
+/// with a given ID, with `operator` `+` or `-`. The first parameter is the
+/// value, the second the ID. With `returning` the statement returns the new
+/// value, which MySQL doesn't support.
+pub(crate) fn counter_sql<T: Schema, V>(
+    operator: &str,
+    column: Column<T, V>,
+    returning: bool,
+) -> Result<String, sqlx::Error> {
+    let dialect = T::dialect();
+    let table = dialect.quote_ident(T::table_name());
+    let name = dialect.quote_ident(column.name());
+    let id_column = format!("{}.{} = ?", table, dialect.quote_ident(T::id_column()));
+    let set = format!("UPDATE {} SET {} = {} {} ?", table, name, name, operator);
+
+    let sql = match (returning, dialect) {
+        (false, _) => format!("{}{}", set, where_clause::<T>(Some(&id_column))),
+        (true, Dialect::MySql) => {
+            return Err(sqlx::Error::Configuration(
+                "MySQL can't return the value of an UPDATE".into(),
+            ))
+        }
+        (true, Dialect::Mssql) => format!(
+            "{} OUTPUT INSERTED.{}{}",
+            set,
+            name,
+            where_clause::<T>(Some(&id_column))
+        ),
+        (true, _) => format!(
+            "{}{} RETURNING {}",
+            set,
+            where_clause::<T>(Some(&id_column)),
+            name
+        ),
+    };
+    Ok(sql)
+}
+
+/// Returns the statement writing `patch` to the records of `T` matching
+/// `filter`. The values of `patch` are bound before the ones of `filter`.
+/// Soft deleted records aren't changed.
+pub(crate) fn update_where_sql<T: Schema, DB: Database>(
+    filter: &Filter<'_, T, DB>,
+    patch: &Patch<'_, T, DB>,
+) -> Result<String, sqlx::Error> {
+    guard(filter, "update_where")?;
+    if patch.sets.is_empty() {
+        return Err(sqlx::Error::Configuration(
+            "update_where was called with an empty patch".into(),
+        ));
+    }
+
+    Ok(format!(
+        "UPDATE {} SET {}{}",
+        T::dialect().quote_ident(T::table_name()),
+        patch.sets.join(", "),
+        where_clause::<T>(Some(&filter.sql))
+    ))
+}
+
+/// Returns the statement deleting the records of `T` matching `filter`, or
+/// soft deleting them if `T` has a `#[deleted_with]` field.
+pub(crate) fn delete_where_sql<T: Schema, DB: Database>(
+    filter: &Filter<'_, T, DB>,
+) -> Result<String, sqlx::Error> {
+    guard(filter, "delete_where")?;
+    let delete = match T::soft_delete_sql() {
+        Some(sql) => sql.to_string(),
+        None => format!("DELETE FROM {}", T::dialect().quote_ident(T::table_name())),
+    };
+    Ok(format!(
+        "{}{}",
+        delete,
+        where_clause::<T>(Some(&filter.sql))
+    ))
+}
+
+/// Refuses a filter matching every record unless it was explicitly allowed.
+fn guard<T, DB: Database>(filter: &Filter<'_, T, DB>, method: &str) -> Result<(), sqlx::Error> {
+    if filter.sql.is_empty() && !filter.allow_all {
+        return Err(sqlx::Error::Configuration(
+            format!(
+                "{} was called with a filter matching every record, use Filter::all().allow_all() to write all of them",
+                method
+            )
+            .into(),
+        ));
+    }
+    Ok(())
+}
+
+/// Returns whether `error` is the violation of a unique or primary key
+/// constraint. MySQL only reports the integrity constraint class, which also
+/// covers foreign keys and NOT NULL columns.
+pub(crate) fn is_unique_violation(error: &sqlx::Error, dialect: Dialect) -> bool {
+    let error = match error.as_database_error() {
+        Some(error) => error,
+        None => return false,
+    };
+    let code = error.code();
+    let code = code.as_deref();
+    let sqlite = matches!(code, Some("2067" | "1555"));
+    let postgres = code == Some("23505");
+    let mysql = code == Some("23000");
+    // MSSQL errors carry no code, only the message of errors 2601 and 2627.
+    let mssql = error.message().starts_with("Violation of PRIMARY KEY")
+        || error.message().starts_with("Violation of UNIQUE KEY")
+        || error.message().starts_with("Cannot insert duplicate key");
+
+    match dialect {
+        Dialect::Sqlite => sqlite,
+        Dialect::Postgres => postgres,
+        Dialect::MySql => mysql,
+        Dialect::Mssql => mssql,
+        Dialect::Any => sqlite || postgres || mysql || mssql,
+    }
+}
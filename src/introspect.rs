@@ -0,0 +1,324 @@
+//! Introspection of live database tables.
+//!
+//! [verify] compares the columns a model was derived with against the table
+//! that actually exists in the database and reports any drift between the
+//! two. The catalog queries are picked based on the model's [Dialect]:
+//! `PRAGMA table_info` on SQLite and `information_schema` on MySQL and
+//! Postgres.
+
+use sqlx::database::HasArguments;
+use sqlx::{ColumnIndex, Database, Decode, Encode, Executor, IntoArguments, Pool, Row, Type};
+
+use crate::schema::Dialect;
+use crate::traits::{CrudFut, Schema};
+
+/// A column as reported by the database catalog.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TableColumn {
+    pub name: String,
+    /// The column type as reported by the database, e.g. `varchar(255)`.
+    pub data_type: String,
+    pub nullable: bool,
+    /// `true` when the database fills in a value if none is provided, either
+    /// through a `DEFAULT` or because the column is auto incrementing.
+    pub has_default: bool,
+    pub primary_key: bool,
+}
+
+/// A column whose nullability differs between the model and the database.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NullabilityMismatch {
+    pub column: String,
+    /// `true` when the field is an `Option<T>`.
+    pub model_nullable: bool,
+    pub database_nullable: bool,
+}
+
+/// The primary key of the model does not match the one of the table.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PrimaryKeyMismatch {
+    pub model: String,
+    pub database: Vec<String>,
+}
+
+/// Differences between a model and its table returned by [verify].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SchemaDiff {
+    pub table_name: String,
+    pub table_exists: bool,
+    /// Model columns missing from the table.
+    pub missing_columns: Vec<String>,
+    /// Table columns unknown to the model which are `NOT NULL` without a
+    /// default and therefore make inserts from the model fail.
+    pub extra_required_columns: Vec<String>,
+    pub nullability_mismatches: Vec<NullabilityMismatch>,
+    pub primary_key_mismatch: Option<PrimaryKeyMismatch>,
+}
+
+impl SchemaDiff {
+    /// Returns `true` when the model matches the table.
+    pub fn is_empty(&self) -> bool {
+        self.table_exists
+            && self.missing_columns.is_empty()
+            && self.extra_required_columns.is_empty()
+            && self.nullability_mismatches.is_empty()
+            && self.primary_key_mismatch.is_none()
+    }
+}
+
+/// Databases whose catalog can be read by [verify]. This is implemented for
+/// every sqlx [Database] able to decode the catalog queries; dialects other
+/// than SQLite, MySQL or Postgres resolve to an error.
+pub trait Introspect: Database {
+    /// Returns the columns of `table_name` in ordinal order. The result is
+    /// empty if the table does not exist.
+    fn table_columns<'e>(
+        pool: &'e Pool<Self>,
+        dialect: Dialect,
+        table_name: &'e str,
+    ) -> CrudFut<'e, Vec<TableColumn>>;
+
+    /// Returns the names of the tables in the current database or schema.
+    fn table_names(pool: &Pool<Self>, dialect: Dialect) -> CrudFut<'_, Vec<String>>;
+
+    /// Returns the names of the indexes of `table_name` created with
+    /// `CREATE INDEX`, excluding the primary key.
+    fn table_indexes<'e>(
+        pool: &'e Pool<Self>,
+        dialect: Dialect,
+        table_name: &'e str,
+    ) -> CrudFut<'e, Vec<String>>;
+}
+
+impl<DB> Introspect for DB
+where
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> <DB as HasArguments<'q>>::Arguments: IntoArguments<'q, DB>,
+    for<'q> &'q str: Encode<'q, DB> + Type<DB>,
+    for<'r> String: Decode<'r, DB> + Type<DB>,
+    for<'r> i64: Decode<'r, DB> + Type<DB>,
+    usize: ColumnIndex<DB::Row>,
+{
+    fn table_columns<'e>(
+        pool: &'e Pool<Self>,
+        dialect: Dialect,
+        table_name: &'e str,
+    ) -> CrudFut<'e, Vec<TableColumn>> {
+        Box::pin(async move {
+            sqlx::query::<DB>(table_columns_sql(dialect)?)
+                .bind(table_name)
+                .try_map(|row: DB::Row| {
+                    Ok(TableColumn {
+                        name: row.try_get(0)?,
+                        data_type: row.try_get(1)?,
+                        nullable: row.try_get::<i64, _>(2)? == 0,
+                        has_default: row.try_get::<i64, _>(3)? != 0,
+                        primary_key: row.try_get::<i64, _>(4)? != 0,
+                    })
+                })
+                .fetch_all(pool)
+                .await
+        })
+    }
+
+    fn table_names(pool: &Pool<Self>, dialect: Dialect) -> CrudFut<'_, Vec<String>> {
+        Box::pin(async move {
+            sqlx::query::<DB>(table_names_sql(dialect)?)
+                .try_map(|row: DB::Row| row.try_get(0))
+                .fetch_all(pool)
+                .await
+        })
+    }
+
+    fn table_indexes<'e>(
+        pool: &'e Pool<Self>,
+        dialect: Dialect,
+        table_name: &'e str,
+    ) -> CrudFut<'e, Vec<String>> {
+        Box::pin(async move {
+            sqlx::query::<DB>(table_indexes_sql(dialect)?)
+                .bind(table_name)
+                .try_map(|row: DB::Row| row.try_get(0))
+                .fetch_all(pool)
+                .await
+        })
+    }
+}
+
+/// Returns the catalog query listing name, type, not null, has default and
+/// primary key flags for every column of the table bound as the only
+/// argument.
+fn table_columns_sql(dialect: Dialect) -> Result<&'static str, sqlx::Error> {
+    match dialect {
+        Dialect::Sqlite => Ok(
+            r#"SELECT name, type, "notnull", dflt_value IS NOT NULL, pk > 0
+            FROM pragma_table_info(?) ORDER BY cid"#,
+        ),
+        Dialect::MySql => Ok(
+            r#"SELECT CAST(column_name AS CHAR), CAST(column_type AS CHAR),
+                CAST(is_nullable = 'NO' AS SIGNED),
+                CAST(column_default IS NOT NULL OR extra LIKE '%auto_increment%' AS SIGNED),
+                CAST(column_key = 'PRI' AS SIGNED)
+            FROM information_schema.columns
+            WHERE table_schema = DATABASE() AND table_name = ?
+            ORDER BY ordinal_position"#,
+        ),
+        Dialect::Postgres => Ok(r#"SELECT c.column_name::TEXT, c.data_type::TEXT,
+                (c.is_nullable = 'NO')::INT::INT8,
+                (c.column_default IS NOT NULL OR c.is_identity = 'YES')::INT::INT8,
+                EXISTS (
+                    SELECT 1 FROM information_schema.table_constraints tc
+                    JOIN information_schema.key_column_usage kcu
+                        ON tc.constraint_name = kcu.constraint_name
+                        AND tc.table_schema = kcu.table_schema
+                    WHERE tc.constraint_type = 'PRIMARY KEY'
+                        AND tc.table_schema = c.table_schema
+                        AND tc.table_name = c.table_name
+                        AND kcu.column_name = c.column_name
+                )::INT::INT8
+            FROM information_schema.columns c
+            WHERE c.table_schema = current_schema() AND c.table_name = $1
+            ORDER BY c.ordinal_position"#),
+        _ => Err(unsupported(dialect)),
+    }
+}
+
+/// Returns the catalog query listing the table names, excluding the tables
+/// used internally by the database.
+fn table_names_sql(dialect: Dialect) -> Result<&'static str, sqlx::Error> {
+    match dialect {
+        Dialect::Sqlite => Ok(r#"SELECT name FROM sqlite_master
+            WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name"#),
+        Dialect::MySql => Ok(
+            r#"SELECT CAST(table_name AS CHAR) FROM information_schema.tables
+            WHERE table_schema = DATABASE() AND table_type = 'BASE TABLE'
+            ORDER BY table_name"#,
+        ),
+        Dialect::Postgres => Ok(r#"SELECT table_name::TEXT FROM information_schema.tables
+            WHERE table_schema = current_schema() AND table_type = 'BASE TABLE'
+            ORDER BY table_name"#),
+        _ => Err(unsupported(dialect)),
+    }
+}
+
+/// Returns the catalog query listing the index names of the table bound as
+/// the only argument.
+fn table_indexes_sql(dialect: Dialect) -> Result<&'static str, sqlx::Error> {
+    match dialect {
+        Dialect::Sqlite => Ok("SELECT name FROM pragma_index_list(?) WHERE origin = 'c'"),
+        Dialect::MySql => Ok(r#"SELECT DISTINCT CAST(index_name AS CHAR)
+            FROM information_schema.statistics
+            WHERE table_schema = DATABASE() AND table_name = ? AND index_name <> 'PRIMARY'"#),
+        Dialect::Postgres => Ok(r#"SELECT i.indexname::TEXT FROM pg_indexes i
+            WHERE i.schemaname = current_schema() AND i.tablename = $1
+                AND NOT EXISTS (
+                    SELECT 1 FROM information_schema.table_constraints tc
+                    WHERE tc.constraint_name = i.indexname
+                        AND tc.table_schema = i.schemaname
+                        AND tc.constraint_type = 'PRIMARY KEY'
+                )"#),
+        _ => Err(unsupported(dialect)),
+    }
+}
+
+fn unsupported(dialect: Dialect) -> sqlx::Error {
+    sqlx::Error::Protocol(format!(
+        "schema introspection is not supported for {:?}",
+        dialect
+    ))
+}
+
+/// Compares the model `T` with its table in the database and returns the
+/// differences found. Use [SchemaDiff::is_empty] to check whether the model
+/// matches the table.
+///
+/// # Example
+///
+/// ```rust
+/// use sqlx::{FromRow, SqlitePool};
+/// use sqlx_crud::{Schema, SqlxCrud};
+///
+/// #[derive(FromRow, SqlxCrud)]
+/// struct Post {
+///     #[id]
+///     post_id: i64,
+///     title: String,
+///     body: Option<String>,
+/// }
+///
+/// # tokio_test::block_on(async {
+/// let pool = SqlitePool::connect(":memory:").await?;
+/// assert!(!sqlx_crud::verify::<Post>(&pool).await?.table_exists);
+///
+/// sqlx::query(Post::create_table_sql()).execute(&pool).await?;
+/// assert!(sqlx_crud::verify::<Post>(&pool).await?.is_empty());
+///
+/// sqlx::query(Post::drop_table_sql()).execute(&pool).await?;
+/// sqlx::query("CREATE TABLE posts (post_id INTEGER PRIMARY KEY, title TEXT, author TEXT NOT NULL)")
+///     .execute(&pool)
+///     .await?;
+/// let diff = sqlx_crud::verify::<Post>(&pool).await?;
+/// assert_eq!(vec!["body".to_string()], diff.missing_columns);
+/// assert_eq!(vec!["author".to_string()], diff.extra_required_columns);
+/// assert_eq!("title", diff.nullability_mismatches[0].column);
+/// # Ok::<(), sqlx::Error>(())
+/// # }).unwrap();
+/// ```
+pub async fn verify<T: Schema>(pool: &Pool<impl Introspect>) -> Result<SchemaDiff, sqlx::Error> {
+    let table_columns = Introspect::table_columns(pool, T::dialect(), T::table_name()).await?;
+    Ok(diff::<T>(&table_columns))
+}
+
+fn diff<T: Schema>(table_columns: &[TableColumn]) -> SchemaDiff {
+    let column_meta = T::column_meta();
+    let find = |name: &str| table_columns.iter().find(|c| c.name == name);
+
+    let missing_columns = column_meta
+        .iter()
+        .filter(|c| find(c.name).is_none())
+        .map(|c| c.name.to_string())
+        .collect();
+
+    let extra_required_columns = table_columns
+        .iter()
+        .filter(|c| !column_meta.iter().any(|m| m.name == c.name))
+        .filter(|c| !c.nullable && !c.has_default && !c.primary_key)
+        .map(|c| c.name.clone())
+        .collect();
+
+    let nullability_mismatches = column_meta
+        .iter()
+        .filter_map(|m| find(m.name).map(|c| (m, c)))
+        // Primary keys can't hold NULL even if the catalog says otherwise.
+        .filter(|(m, c)| m.nullable != (c.nullable && !c.primary_key))
+        .map(|(m, c)| NullabilityMismatch {
+            column: m.name.to_string(),
+            model_nullable: m.nullable,
+            database_nullable: c.nullable && !c.primary_key,
+        })
+        .collect();
+
+    let primary_key = table_columns
+        .iter()
+        .filter(|c| c.primary_key)
+        .map(|c| c.name.clone())
+        .collect::<Vec<_>>();
+    let primary_key_mismatch = if !table_columns.is_empty() && primary_key != [T::id_column()] {
+        Some(PrimaryKeyMismatch {
+            model: T::id_column().to_string(),
+            database: primary_key,
+        })
+    } else {
+        None
+    };
+
+    SchemaDiff {
+        table_name: T::table_name().to_string(),
+        table_exists: !table_columns.is_empty(),
+        missing_columns,
+        extra_required_columns,
+        nullability_mismatches,
+        primary_key_mismatch,
+    }
+}
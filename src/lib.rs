@@ -154,12 +154,33 @@
 //! * Crud::create() should return the assigned ID
 //! * Add a field attribute to ignore fields
 
+pub mod audit;
+#[cfg(feature = "cli")]
+pub mod cli;
+pub mod codegen;
+pub mod events;
+pub mod filter;
+pub mod introspect;
+pub mod lock;
+pub mod migrate;
+pub mod outbox;
+pub mod registry;
+pub mod relations;
 pub mod schema;
+pub mod select;
+pub mod timestamp;
 pub mod traits;
+pub mod validate;
+#[doc(hidden)]
+pub mod write;
 
-pub use sqlx_crud_macros::SqlxCrud;
-pub use timed_fields::add_timed_fields;
-pub use traits::{Crud, Schema};
+pub use introspect::verify;
+#[doc(hidden)]
+pub use inventory;
+pub use registry::models;
+pub use sqlx_crud_macros::{timestamps, SqlxCrud};
+pub use traits::{Crud, CrudHooks, Operation, Schema};
+pub use validate::Validate;
 
 #[macro_export]
 #[doc(hidden)]
@@ -0,0 +1,157 @@
+//! Row locks taken by SELECT queries inside a transaction.
+//!
+//! [Crud::by_id_for_update](crate::Crud::by_id_for_update) selects a record
+//! and locks it until the transaction ends, so it can be read and updated
+//! without a concurrent write in between.
+//! [Crud::lock_next](crate::Crud::lock_next) locks the next records matching
+//! a [filter](crate::filter) in ID order, skipping the records locked by
+//! other transactions, which lets several workers take jobs from the same
+//! queue table. [Crud::lock_where](crate::Crud::lock_where) chooses the
+//! [Lock] mode. The methods take the transaction itself, they can't be
+//! called with a pool.
+//!
+//! Postgres and MySQL 8 lock with `FOR UPDATE`, `FOR UPDATE SKIP LOCKED` or
+//! `FOR UPDATE NOWAIT`, MSSQL with the `UPDLOCK`, `READPAST` and `NOWAIT`
+//! table hints.
+//!
+//! SQLite has no row locks: the queries are plain SELECTs and the whole
+//! database is locked by the first write of a transaction. Two transactions
+//! can therefore read the same record, and the second one to write fails
+//! with `SQLITE_BUSY` instead of waiting or skipping the record. Workers on
+//! SQLite should write, e.g. mark the job taken, right after locking and
+//! retry on busy errors, or run one at a time.
+//!
+//! # Example
+//!
+//! ```rust
+//! use sqlx::{FromRow, SqlitePool};
+//! use sqlx_crud::{Crud, Schema, SqlxCrud};
+//!
+//! #[derive(FromRow, SqlxCrud)]
+//! struct Job {
+//!     job_id: i64,
+//!     taken: bool,
+//! }
+//!
+//! # tokio_test::block_on(async {
+//! let pool = SqlitePool::connect(":memory:").await?;
+//! sqlx::query(Job::create_table_sql()).execute(&pool).await?;
+//! for job_id in 1..=3 {
+//!     Job { job_id, taken: false }.create(&pool).await?;
+//! }
+//!
+//! let mut tx = pool.begin().await?;
+//! let jobs = Job::lock_next(&mut tx, Job::cols().taken.eq(false), 2).await?;
+//! assert_eq!(vec![1, 2], jobs.iter().map(|j| j.job_id).collect::<Vec<_>>());
+//! for job in jobs {
+//!     sqlx::query("UPDATE jobs SET taken = TRUE WHERE job_id = ?")
+//!         .bind(job.job_id)
+//!         .execute(&mut tx)
+//!         .await?;
+//! }
+//! tx.commit().await?;
+//!
+//! let mut tx = pool.begin().await?;
+//! let job = Job::by_id_for_update(&mut tx, 3).await?.unwrap();
+//! assert!(!job.taken);
+//! tx.commit().await?;
+//! # Ok::<(), sqlx::Error>(())
+//! # }).unwrap();
+//! ```
+
+use sqlx::database::HasArguments;
+use sqlx::{Database, Encode, Executor, FromRow, IntoArguments, Type};
+
+use crate::filter::{self, Filter};
+use crate::schema::Dialect;
+use crate::traits::Schema;
+
+/// How a locking query treats records locked by other transactions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Lock {
+    /// Waits until the records are unlocked.
+    Wait,
+    /// Leaves out the locked records.
+    SkipLocked,
+    /// Fails if any of the records is locked.
+    NoWait,
+}
+
+/// Returns the statement selecting and locking the records of `T` matching
+/// `condition` in ID order, at most `limit` of them.
+pub(crate) fn lock_sql<T: Schema>(lock: Lock, condition: &str, limit: Option<u64>) -> String {
+    let dialect = T::dialect();
+    let table = dialect.quote_ident(T::table_name());
+    let columns = T::columns()
+        .iter()
+        .map(|column| format!("{}.{}", table, dialect.quote_ident(column)))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let order = format!("{}.{}", table, dialect.quote_ident(T::id_column()));
+    let condition = filter::where_clause::<T>(Some(condition));
+
+    match dialect {
+        Dialect::Mssql => {
+            let hints = match lock {
+                Lock::Wait => "UPDLOCK, ROWLOCK",
+                Lock::SkipLocked => "UPDLOCK, ROWLOCK, READPAST",
+                Lock::NoWait => "UPDLOCK, ROWLOCK, NOWAIT",
+            };
+            let top = limit.map_or(String::new(), |limit| format!("TOP ({}) ", limit));
+            format!(
+                "SELECT {}{} FROM {} WITH ({}){} ORDER BY {}",
+                top, columns, table, hints, condition, order
+            )
+        }
+        _ => {
+            let limit = limit.map_or(String::new(), |limit| format!(" LIMIT {}", limit));
+            let lock = match (dialect, lock) {
+                (Dialect::Sqlite | Dialect::Any, _) => "",
+                (_, Lock::Wait) => " FOR UPDATE",
+                (_, Lock::SkipLocked) => " FOR UPDATE SKIP LOCKED",
+                (_, Lock::NoWait) => " FOR UPDATE NOWAIT",
+            };
+            format!(
+                "SELECT {} FROM {}{} ORDER BY {}{}{}",
+                columns, table, condition, order, limit, lock
+            )
+        }
+    }
+}
+
+/// Runs the locking statement `sql` selecting the record of `T` with the ID.
+pub(crate) async fn fetch_by_id<DB, T>(
+    conn: &mut DB::Connection,
+    sql: String,
+    id: T::Id,
+) -> Result<Option<T>, sqlx::Error>
+where
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> <DB as HasArguments<'q>>::Arguments: IntoArguments<'q, DB>,
+    T: Schema + Send + Unpin + for<'r> FromRow<'r, DB::Row>,
+    for<'q> T::Id: Encode<'q, DB> + Type<DB>,
+{
+    sqlx::query_as::<DB, T>(&sql)
+        .bind(id)
+        .fetch_optional(conn)
+        .await
+}
+
+/// Runs the locking statement `sql` selecting the records of `T` matching
+/// `filter`.
+pub(crate) async fn fetch_where<DB, T>(
+    conn: &mut DB::Connection,
+    sql: String,
+    filter: Filter<'_, T, DB>,
+) -> Result<Vec<T>, sqlx::Error>
+where
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> <DB as HasArguments<'q>>::Arguments: IntoArguments<'q, DB>,
+    T: Send + Unpin + for<'r> FromRow<'r, DB::Row>,
+{
+    sqlx::query_as_with::<DB, T, _>(&sql, filter.arguments())
+        .fetch_all(conn)
+        .await
+}
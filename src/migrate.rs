@@ -0,0 +1,324 @@
+//! Generates migration files from the difference between the registered
+//! models and the current database schema.
+//!
+//! The current schema is described by a [Snapshot], taken either from a live
+//! database with [Snapshot::from_database] or from the snapshot file written
+//! next to the migrations after each generated migration. [plan] turns the
+//! difference in to DDL statements and [write_migration] saves them as
+//! `<timestamp>_<name>.sql`, the same layout `sqlx migrate add` uses.
+//!
+//! Only additive changes are planned: missing tables, missing columns and
+//! indexes declared with `#[index]`. Indexes following the `idx_<table>_`
+//! naming scheme which are no longer declared are dropped. Removed columns
+//! and type changes are left to hand written migrations.
+//!
+//! Columns added to an existing table must accept its rows. `NOT NULL`
+//! columns without a default of their own get the zero value of their type,
+//! `0`, `FALSE` or `''`. Columns of other types and primary keys can't be
+//! added, [plan] fails with a [PlanError] for them.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use sqlx::Pool;
+
+use crate::introspect::Introspect;
+use crate::registry::Model;
+use crate::schema::{ColumnMeta, Dialect};
+
+/// File name of the snapshot stored in the migrations directory. sqlx
+/// ignores files not named `<version>_<description>.sql`.
+pub const SNAPSHOT_FILE: &str = ".sqlx-crud-snapshot";
+
+/// Columns and indexes of a table known to exist.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SnapshotTable {
+    pub columns: Vec<String>,
+    pub indexes: Vec<String>,
+}
+
+/// The tables, columns and indexes a migration is planned against.
+///
+/// The text representation used by the snapshot file lists one `table`,
+/// `column` or `index` entry per line:
+///
+/// ```text
+/// table posts
+/// column post_id
+/// column title
+/// index idx_posts_title
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Snapshot {
+    pub tables: BTreeMap<String, SnapshotTable>,
+}
+
+impl Snapshot {
+    /// Returns the schema the models describe, which is the state of the
+    /// database once every planned migration ran.
+    pub fn from_models(models: &[&Model]) -> Self {
+        let tables = models
+            .iter()
+            .map(|m| {
+                let table = SnapshotTable {
+                    columns: m.columns.iter().map(|c| c.to_string()).collect(),
+                    indexes: m.indexes.iter().map(|i| i.name.to_string()).collect(),
+                };
+                (m.table_name.to_string(), table)
+            })
+            .collect();
+
+        Self { tables }
+    }
+
+    /// Reads the columns and indexes of the models' tables from the
+    /// database. Tables which don't exist are left out.
+    pub async fn from_database<DB: Introspect>(
+        pool: &Pool<DB>,
+        models: &[&Model],
+    ) -> Result<Self, sqlx::Error> {
+        let mut tables = BTreeMap::new();
+        for model in models {
+            let columns = DB::table_columns(pool, model.dialect, model.table_name).await?;
+            if columns.is_empty() {
+                continue;
+            }
+            let indexes = DB::table_indexes(pool, model.dialect, model.table_name).await?;
+            let table = SnapshotTable {
+                columns: columns.into_iter().map(|c| c.name).collect(),
+                indexes,
+            };
+            tables.insert(model.table_name.to_string(), table);
+        }
+
+        Ok(Self { tables })
+    }
+
+    /// Parses the text representation written by [Snapshot::save].
+    pub fn parse(snapshot: &str) -> Self {
+        let mut tables = BTreeMap::<String, SnapshotTable>::new();
+        let mut current = None;
+        for line in snapshot.lines() {
+            match line.trim().split_once(' ') {
+                Some(("table", name)) => {
+                    tables.entry(name.to_string()).or_default();
+                    current = Some(name.to_string());
+                }
+                Some(("column", name)) => {
+                    if let Some(table) = current.as_ref().and_then(|t| tables.get_mut(t)) {
+                        table.columns.push(name.to_string());
+                    }
+                }
+                Some(("index", name)) => {
+                    if let Some(table) = current.as_ref().and_then(|t| tables.get_mut(t)) {
+                        table.indexes.push(name.to_string());
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Self { tables }
+    }
+
+    /// Loads the snapshot stored in the migrations directory `dir`. Returns
+    /// an empty snapshot if none was saved yet.
+    pub fn load(dir: impl AsRef<Path>) -> io::Result<Self> {
+        match fs::read_to_string(dir.as_ref().join(SNAPSHOT_FILE)) {
+            Ok(snapshot) => Ok(Self::parse(&snapshot)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Saves the snapshot in the migrations directory `dir`.
+    pub fn save(&self, dir: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(dir.as_ref().join(SNAPSHOT_FILE), self.to_string())
+    }
+}
+
+impl fmt::Display for Snapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, table) in &self.tables {
+            writeln!(f, "table {}", name)?;
+            for column in &table.columns {
+                writeln!(f, "column {}", column)?;
+            }
+            for index in &table.indexes {
+                writeln!(f, "index {}", index)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A column [plan] can't add to an existing table.
+#[derive(Debug, thiserror::Error)]
+pub enum PlanError {
+    #[error("can't add the primary key column {column} to the existing table {table}")]
+    PrimaryKey { table: String, column: String },
+    #[error(
+        "can't add the NOT NULL column {column} to the existing table {table} without a default, \
+         make it an Option or write the migration by hand"
+    )]
+    NoDefault { table: String, column: String },
+}
+
+/// Returns the statements migrating `current` to the schema described by
+/// `models`. The result is empty when there is nothing to migrate.
+///
+/// # Example
+///
+/// ```rust
+/// use sqlx::FromRow;
+/// use sqlx_crud::migrate::{self, Snapshot};
+/// use sqlx_crud::SqlxCrud;
+///
+/// #[derive(FromRow, SqlxCrud)]
+/// struct Article {
+///     article_id: i64,
+///     #[index]
+///     title: String,
+///     summary: Option<String>,
+///     views: i64,
+/// }
+///
+/// let models = sqlx_crud::models();
+/// let current = Snapshot::parse("table articles\ncolumn article_id\ncolumn title\n");
+///
+/// assert_eq!(
+///     vec![
+///         r#"ALTER TABLE "articles" ADD COLUMN "summary" TEXT NULL"#,
+///         r#"ALTER TABLE "articles" ADD COLUMN "views" INTEGER NOT NULL DEFAULT 0"#,
+///         r#"CREATE INDEX "idx_articles_title" ON "articles" ("title")"#,
+///     ],
+///     migrate::plan(&models, &current)?
+/// );
+/// assert!(migrate::plan(&models, &Snapshot::from_models(&models))?.is_empty());
+///
+/// let current = Snapshot::parse("table articles\ncolumn title\n");
+/// assert!(migrate::plan(&models, &current).is_err());
+/// # Ok::<(), migrate::PlanError>(())
+/// ```
+pub fn plan(models: &[&Model], current: &Snapshot) -> Result<Vec<String>, PlanError> {
+    let mut statements = Vec::new();
+    for model in models {
+        let Some(table) = current.tables.get(model.table_name) else {
+            statements.push(model.create_table_sql.to_string());
+            statements.extend(model.indexes.iter().map(|i| i.create_sql.to_string()));
+            continue;
+        };
+
+        for column in model
+            .column_meta
+            .iter()
+            .filter(|c| !table.columns.iter().any(|t| t == c.name))
+        {
+            statements.push(add_column_sql(model, column)?);
+        }
+        statements.extend(
+            model
+                .indexes
+                .iter()
+                .filter(|i| !table.indexes.iter().any(|t| t == i.name))
+                .map(|i| i.create_sql.to_string()),
+        );
+        let managed_prefix = format!("idx_{}_", model.table_name);
+        statements.extend(
+            table
+                .indexes
+                .iter()
+                .filter(|t| t.starts_with(&managed_prefix))
+                .filter(|t| !model.indexes.iter().any(|i| &i.name == t))
+                .map(|t| model.dialect.drop_index_sql(model.table_name, t)),
+        );
+    }
+
+    Ok(statements)
+}
+
+/// Returns the statement adding `column` to the existing table of `model`.
+fn add_column_sql(model: &Model, column: &ColumnMeta) -> Result<String, PlanError> {
+    if column.is_id || column.auto_increment {
+        return Err(PlanError::PrimaryKey {
+            table: model.table_name.to_string(),
+            column: column.name.to_string(),
+        });
+    }
+
+    let mut definition = column.definition.to_string();
+    if !column.nullable && !definition.contains(" DEFAULT ") {
+        let default =
+            zero_default(model.dialect, column.sql_type).ok_or_else(|| PlanError::NoDefault {
+                table: model.table_name.to_string(),
+                column: column.name.to_string(),
+            })?;
+        definition = format!("{} DEFAULT {}", definition, default);
+    }
+
+    let add = match model.dialect {
+        Dialect::Mssql => "ADD",
+        _ => "ADD COLUMN",
+    };
+    Ok(format!(
+        "ALTER TABLE {} {} {}",
+        model.dialect.quote_ident(model.table_name),
+        add,
+        definition
+    ))
+}
+
+/// Returns the zero value of `sql_type` as an SQL literal, used as the
+/// default of `NOT NULL` columns added to tables which may have rows.
+fn zero_default(dialect: Dialect, sql_type: &str) -> Option<&'static str> {
+    match sql_type.split(['(', ' ']).next().unwrap_or_default() {
+        "BIT" => Some("0"),
+        "BOOLEAN" => Some("FALSE"),
+        "TINYINT" | "SMALLINT" | "INT" | "INTEGER" | "BIGINT" | "REAL" | "FLOAT" | "DOUBLE"
+        | "NUMERIC" | "DECIMAL" => Some("0"),
+        // MySQL only accepts literal defaults on VARCHAR, not TEXT.
+        "TEXT" if dialect == Dialect::MySql => None,
+        "TEXT" | "VARCHAR" | "NVARCHAR" => Some("''"),
+        _ => None,
+    }
+}
+
+/// Writes `statements` to `<dir>/<timestamp>_<name>.sql` and returns the path
+/// of the new file. Spaces in `name` are replaced with underscores.
+pub fn write_migration(
+    dir: impl AsRef<Path>,
+    name: &str,
+    statements: &[String],
+) -> io::Result<PathBuf> {
+    let dir = dir.as_ref();
+    fs::create_dir_all(dir)?;
+
+    let path = dir.join(format!(
+        "{}_{}.sql",
+        timestamp(SystemTime::now()),
+        name.trim().replace(' ', "_")
+    ));
+    let mut contents = String::from("-- Generated by sqlx-crud\n");
+    for statement in statements {
+        contents.push_str(statement);
+        contents.push_str(";\n");
+    }
+    fs::write(&path, contents)?;
+
+    Ok(path)
+}
+
+/// Formats `time` as `YYYYMMDDHHMMSS` in UTC like sqlx-cli does for
+/// migration versions.
+fn timestamp(time: SystemTime) -> String {
+    let [year, month, day, hour, minute, second] = crate::timestamp::utc(time);
+
+    format!(
+        "{:04}{:02}{:02}{:02}{:02}{:02}",
+        year, month, day, hour, minute, second
+    )
+}
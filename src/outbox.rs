@@ -0,0 +1,297 @@
+//! Transactional outbox of the changes written by the [Crud](crate::Crud)
+//! methods.
+//!
+//! Models tagged with `#[crud(outbox)]` insert an event into the `outbox`
+//! table for every [create](crate::Crud::create),
+//! [update](crate::Crud::update) and [delete](crate::Crud::delete), in the
+//! same transaction as the change. An event is never lost once the change is
+//! committed, even if the process crashes before it is published. Writes
+//! changing no row, such as an update of a soft deleted record, insert no
+//! event.
+//!
+//! An [OutboxRelay] reads the undelivered events in order, hands them to an
+//! async sink, e.g. a message broker client, and marks each event delivered
+//! after the sink accepted it. An event whose sink call failed, or that was
+//! handed to the sink by a relay which crashed before marking it, is handed
+//! out again, so sinks have to tolerate duplicates.
+//!
+//! Like the [audit log](crate::audit), outbox models have to implement
+//! `serde::Serialize` and their id type `Display`. The payload of an event
+//! is the row after the change as JSON, or the row before it for hard
+//! deletes. The table is created with [create_table_sql].
+//!
+//! # Example
+//!
+//! ```rust
+//! use serde::Serialize;
+//! use sqlx::{FromRow, SqlitePool};
+//! use sqlx_crud::outbox::{self, OutboxRelay};
+//! use sqlx_crud::{Crud, Operation, Schema, SqlxCrud};
+//!
+//! #[derive(FromRow, Serialize, SqlxCrud)]
+//! #[crud(outbox)]
+//! struct Order {
+//!     #[id]
+//!     #[auto_increment]
+//!     order_id: i64,
+//!     total: i64,
+//! }
+//!
+//! # tokio_test::block_on(async {
+//! let pool = SqlitePool::connect(":memory:").await?;
+//! sqlx::query(Order::create_table_sql()).execute(&pool).await?;
+//! sqlx::query(&outbox::create_table_sql(Order::dialect())).execute(&pool).await?;
+//!
+//! Order { order_id: 0, total: 42 }.create(&pool).await?;
+//! Order::by_id(&pool, 1).await?.unwrap().delete(&pool).await?;
+//!
+//! let relay = OutboxRelay::new(pool.clone(), Order::dialect());
+//!
+//! // A failing sink leaves the event undelivered.
+//! let failed = relay.relay_once(|_| async { Err("broker unavailable") }).await;
+//! assert!(matches!(failed, Err(outbox::Error::Sink(_))));
+//!
+//! let mut published = Vec::new();
+//! let delivered = relay
+//!     .relay_once(|event| {
+//!         published.push(event);
+//!         async { Ok::<_, sqlx::Error>(()) }
+//!     })
+//!     .await?;
+//! assert_eq!(2, delivered);
+//! assert_eq!(Operation::Create, published[0].operation);
+//! assert_eq!(r#"{"order_id":1,"total":42}"#, published[0].payload);
+//! assert_eq!(Operation::Delete, published[1].operation);
+//!
+//! assert_eq!(0, relay.relay_once(|_| async { Ok::<_, sqlx::Error>(()) }).await?);
+//! # Ok::<(), outbox::Error>(())
+//! # }).unwrap();
+//! ```
+
+use std::error::Error as StdError;
+use std::time::{Duration, SystemTime};
+
+use futures::Future;
+use sqlx::database::HasArguments;
+use sqlx::{ColumnIndex, Database, Decode, Encode, Executor, IntoArguments, Pool, Row, Type};
+
+use crate::schema::{Dialect, LogColumn};
+use crate::traits::{Operation, Schema};
+
+/// Name of the outbox table.
+pub const TABLE_NAME: &str = "outbox";
+
+/// Errors returned by an [OutboxRelay].
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("outbox sink failed: {0}")]
+    Sink(Box<dyn StdError + Send + Sync>),
+    #[error(transparent)]
+    Sqlx(#[from] sqlx::Error),
+}
+
+/// An event of the outbox handed to the sink of an [OutboxRelay].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OutboxEvent {
+    pub outbox_id: i64,
+    pub table_name: String,
+    /// The primary key of the changed record formatted with `Display`.
+    pub record_id: String,
+    pub operation: Operation,
+    /// The changed row as JSON.
+    pub payload: String,
+    /// UTC time of the change formatted as `YYYY-MM-DD HH:MM:SS`.
+    pub created_at: String,
+}
+
+/// Returns the statement creating the outbox table for `dialect`.
+pub fn create_table_sql(dialect: Dialect) -> String {
+    dialect.create_log_table_sql(
+        TABLE_NAME,
+        "outbox_id",
+        &[
+            ("table_name", LogColumn::Text),
+            ("record_id", LogColumn::Text),
+            ("operation", LogColumn::Text),
+            ("payload", LogColumn::Document),
+            ("created_at", LogColumn::Text),
+            ("delivered_at", LogColumn::OptionalText),
+        ],
+    )
+}
+
+/// Hands the undelivered events of the outbox to a sink and marks them
+/// delivered.
+pub struct OutboxRelay<DB: Database> {
+    pool: Pool<DB>,
+    dialect: Dialect,
+    batch_size: usize,
+    interval: Duration,
+}
+
+impl<DB> OutboxRelay<DB>
+where
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> <DB as HasArguments<'q>>::Arguments: IntoArguments<'q, DB>,
+    for<'q> String: Encode<'q, DB> + Type<DB>,
+    for<'q> i64: Encode<'q, DB> + Type<DB>,
+    for<'r> String: Decode<'r, DB> + Type<DB>,
+    for<'r> i64: Decode<'r, DB> + Type<DB>,
+    usize: ColumnIndex<DB::Row>,
+{
+    /// Returns a relay reading the outbox of `pool` in batches of 100 events
+    /// and polling every second when the outbox is empty.
+    pub fn new(pool: Pool<DB>, dialect: Dialect) -> Self {
+        Self {
+            pool,
+            dialect,
+            batch_size: 100,
+            interval: Duration::from_secs(1),
+        }
+    }
+
+    /// Sets the maximum number of events read at once.
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Sets the time [run](Self::run) waits when the outbox is empty.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Hands the next batch of undelivered events to `sink` in order and
+    /// returns the number of events delivered. Stops at the first event the
+    /// sink fails on, which stays undelivered.
+    pub async fn relay_once<F, Fut, E>(&self, mut sink: F) -> Result<usize, Error>
+    where
+        F: FnMut(OutboxEvent) -> Fut,
+        Fut: Future<Output = Result<(), E>>,
+        E: Into<Box<dyn StdError + Send + Sync>>,
+    {
+        let q = |ident| self.dialect.quote_ident(ident);
+        let columns = [
+            "outbox_id",
+            "table_name",
+            "record_id",
+            "operation",
+            "payload",
+            "created_at",
+        ]
+        .map(q)
+        .join(", ");
+        let undelivered = format!("{} IS NULL", q("delivered_at"));
+        let select_sql = match self.dialect {
+            Dialect::Mssql => format!(
+                "SELECT TOP ({}) {} FROM {} WHERE {} ORDER BY {}",
+                self.batch_size,
+                columns,
+                q(TABLE_NAME),
+                undelivered,
+                q("outbox_id")
+            ),
+            _ => format!(
+                "SELECT {} FROM {} WHERE {} ORDER BY {} LIMIT {}",
+                columns,
+                q(TABLE_NAME),
+                undelivered,
+                q("outbox_id"),
+                self.batch_size
+            ),
+        };
+        let deliver_sql = format!(
+            "UPDATE {} SET {} = ? WHERE {} = ?",
+            q(TABLE_NAME),
+            q("delivered_at"),
+            q("outbox_id")
+        );
+
+        let events = sqlx::query::<DB>(&select_sql)
+            .try_map(|row: DB::Row| {
+                Ok(OutboxEvent {
+                    outbox_id: row.try_get(0)?,
+                    table_name: row.try_get(1)?,
+                    record_id: row.try_get(2)?,
+                    operation: row
+                        .try_get::<String, _>(3)?
+                        .parse()
+                        .map_err(|e: String| sqlx::Error::Decode(e.into()))?,
+                    payload: row.try_get(4)?,
+                    created_at: row.try_get(5)?,
+                })
+            })
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut delivered = 0;
+        for event in events {
+            let outbox_id = event.outbox_id;
+            sink(event).await.map_err(|e| Error::Sink(e.into()))?;
+            sqlx::query::<DB>(&deliver_sql)
+                .bind(crate::timestamp::format_utc(SystemTime::now()))
+                .bind(outbox_id)
+                .execute(&self.pool)
+                .await?;
+            delivered += 1;
+        }
+
+        Ok(delivered)
+    }
+
+    /// Relays events with [relay_once](Self::relay_once) until an error
+    /// occurs, waiting for the interval whenever the outbox is empty.
+    /// Requires a tokio runtime.
+    pub async fn run<F, Fut, E>(&self, mut sink: F) -> Result<(), Error>
+    where
+        F: FnMut(OutboxEvent) -> Fut,
+        Fut: Future<Output = Result<(), E>>,
+        E: Into<Box<dyn StdError + Send + Sync>>,
+    {
+        loop {
+            if self.relay_once(&mut sink).await? == 0 {
+                tokio::time::sleep(self.interval).await;
+            }
+        }
+    }
+}
+
+/// Inserts an outbox event of the record `record_id` of `T`.
+pub(crate) async fn insert<DB, T>(
+    conn: &mut DB::Connection,
+    record_id: String,
+    operation: Operation,
+    payload: String,
+) -> Result<(), sqlx::Error>
+where
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> <DB as HasArguments<'q>>::Arguments: IntoArguments<'q, DB>,
+    for<'q> String: Encode<'q, DB> + Type<DB>,
+    T: Schema,
+{
+    let dialect = T::dialect();
+    let q = |ident| dialect.quote_ident(ident);
+    let sql = format!(
+        "INSERT INTO {} ({}, {}, {}, {}, {}) VALUES (?, ?, ?, ?, ?)",
+        q(TABLE_NAME),
+        q("table_name"),
+        q("record_id"),
+        q("operation"),
+        q("payload"),
+        q("created_at"),
+    );
+
+    sqlx::query::<DB>(&sql)
+        .bind(T::table_name().to_string())
+        .bind(record_id)
+        .bind(operation.as_str().to_string())
+        .bind(payload)
+        .bind(crate::timestamp::format_utc(SystemTime::now()))
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,58 @@
+//! Link time registry of every model deriving [SqlxCrud] in the binary.
+//!
+//! Each derived struct submits a [Model] describing its table, which can be
+//! enumerated at runtime with [models]. This is handy for schema dumps,
+//! health checks that verify each table exists, and generic admin tooling.
+//!
+//! [SqlxCrud]: ../derive.SqlxCrud.html
+
+use crate::schema::{ColumnMeta, Dialect, IndexMeta};
+
+/// Type erased view of a model's [Metadata](crate::schema::Metadata).
+#[derive(Debug)]
+pub struct Model {
+    /// Name of the Rust struct the model was derived from.
+    pub name: &'static str,
+    pub dialect: Dialect,
+    pub table_name: &'static str,
+    pub id_column: &'static str,
+    pub columns: &'static [&'static str],
+    pub column_meta: &'static [ColumnMeta<'static>],
+    pub indexes: &'static [IndexMeta<'static>],
+    pub select_sql: &'static str,
+    pub select_by_id_sql: &'static str,
+    pub insert_sql: &'static str,
+    pub update_by_id_sql: &'static str,
+    pub delete_by_id_sql: &'static str,
+    pub create_table_sql: &'static str,
+    pub drop_table_sql: &'static str,
+}
+
+inventory::collect!(Model);
+
+/// Returns every registered model ordered by table name.
+///
+/// # Example
+///
+/// ```rust
+/// use sqlx::FromRow;
+/// use sqlx_crud::SqlxCrud;
+///
+/// #[derive(FromRow, SqlxCrud)]
+/// struct Invoice {
+///     invoice_id: i64,
+///     amount: i64,
+/// }
+///
+/// let invoices = sqlx_crud::models()
+///     .into_iter()
+///     .find(|m| m.table_name == "invoices")
+///     .unwrap();
+/// assert_eq!("Invoice", invoices.name);
+/// assert_eq!(&["invoice_id", "amount"], invoices.columns);
+/// ```
+pub fn models() -> Vec<&'static Model> {
+    let mut models = inventory::iter::<Model>.into_iter().collect::<Vec<_>>();
+    models.sort_by_key(|m| m.table_name);
+    models
+}
@@ -0,0 +1,404 @@
+//! Relationships between models.
+//!
+//! # belongs_to
+//!
+//! A foreign key field tagged with `#[belongs_to(Parent)]` generates two
+//! methods named after the field without its `_id` suffix, or after the
+//! parent type if the field has no such suffix:
+//!
+//! * `post.user(pool)` looks up the parent of a record by id.
+//! * `Post::load_users(pool, &posts)` loads the parents of many records in
+//!   one `IN` query built from the parent's [select_sql](crate::Schema::select_sql).
+//!
+//! The field has the type of the parent's id, or an `Option` of it for
+//! optional relationships.
+//!
+//! ```rust
+//! use sqlx::{FromRow, SqlitePool};
+//! use sqlx_crud::{Crud, Schema, SqlxCrud};
+//!
+//! #[derive(FromRow, SqlxCrud)]
+//! struct User {
+//!     user_id: i64,
+//!     name: String,
+//! }
+//!
+//! #[derive(FromRow, SqlxCrud)]
+//! struct Post {
+//!     post_id: i64,
+//!     #[belongs_to(User)]
+//!     user_id: i64,
+//!     #[belongs_to(User)]
+//!     editor_id: Option<i64>,
+//! }
+//!
+//! # tokio_test::block_on(async {
+//! let pool = SqlitePool::connect(":memory:").await?;
+//! sqlx::query(User::create_table_sql()).execute(&pool).await?;
+//! sqlx::query(Post::create_table_sql()).execute(&pool).await?;
+//!
+//! for (user_id, name) in [(1, "alice"), (2, "bob")] {
+//!     User { user_id, name: name.to_string() }.create(&pool).await?;
+//! }
+//! let posts = vec![
+//!     Post { post_id: 1, user_id: 1, editor_id: None },
+//!     Post { post_id: 2, user_id: 2, editor_id: Some(1) },
+//! ];
+//!
+//! assert_eq!("bob", posts[1].user(&pool).await?.unwrap().name);
+//! assert!(posts[0].editor(&pool).await?.is_none());
+//!
+//! let users = Post::load_users(&pool, &posts).await?;
+//! assert_eq!(2, users.len());
+//! let editors = Post::load_editors(&pool, &posts).await?;
+//! assert_eq!("alice", editors[0].name);
+//! # Ok::<(), sqlx::Error>(())
+//! # }).unwrap();
+//! ```
+//!
+//! # has_many
+//!
+//! The struct attribute `#[has_many(Child, foreign_key = "...")]` generates
+//! two methods named after the plural of the child type, or `name = "..."`:
+//!
+//! * `user.posts(pool)` loads the children of a record.
+//! * `User::with_posts(pool, users)` pairs every record with its children,
+//!   loaded with one `IN` query built from the child's
+//!   [select_sql](crate::Schema::select_sql).
+//!
+//! The foreign key defaults to the snake case name of the struct followed by
+//! `_id`. It is a field of the child of the type of the parent's id, or an
+//! `Option` of it. The id type has to implement `Hash` and `Eq`.
+//!
+//! ```rust
+//! use sqlx::{FromRow, SqlitePool};
+//! use sqlx_crud::{Crud, Schema, SqlxCrud};
+//!
+//! #[derive(FromRow, SqlxCrud)]
+//! #[has_many(Post)]
+//! #[has_many(Post, foreign_key = "editor_id", name = "edited_posts")]
+//! struct User {
+//!     user_id: i64,
+//!     name: String,
+//! }
+//!
+//! #[derive(FromRow, SqlxCrud)]
+//! struct Post {
+//!     post_id: i64,
+//!     user_id: i64,
+//!     editor_id: Option<i64>,
+//! }
+//!
+//! # tokio_test::block_on(async {
+//! let pool = SqlitePool::connect(":memory:").await?;
+//! sqlx::query(User::create_table_sql()).execute(&pool).await?;
+//! sqlx::query(Post::create_table_sql()).execute(&pool).await?;
+//!
+//! for (user_id, name) in [(1, "alice"), (2, "bob")] {
+//!     User { user_id, name: name.to_string() }.create(&pool).await?;
+//! }
+//! for (post_id, user_id, editor_id) in [(1, 1, None), (2, 1, Some(2)), (3, 2, None)] {
+//!     Post { post_id, user_id, editor_id }.create(&pool).await?;
+//! }
+//!
+//! let alice = User::by_id(&pool, 1).await?.unwrap();
+//! assert_eq!(2, alice.posts(&pool).await?.len());
+//!
+//! let users = User::with_edited_posts(&pool, User::all(&pool).await?).await?;
+//! assert_eq!(vec![0, 1], users.iter().map(|(_, posts)| posts.len()).collect::<Vec<_>>());
+//! assert_eq!(2, users[1].1[0].post_id);
+//! # Ok::<(), sqlx::Error>(())
+//! # }).unwrap();
+//! ```
+//!
+//! # many_to_many
+//!
+//! The struct attribute `#[many_to_many(Remote, through = "...", local = "...",
+//! remote = "...")]` relates two models through a join table holding the ids
+//! of both. `through` defaults to the snake case name of the struct followed
+//! by the plural of the remote type, `local` and `remote` to the snake case
+//! names of the two types followed by `_id`. The join table isn't a model, it
+//! only needs those two columns. For `#[many_to_many(Role)]` on `User`:
+//!
+//! * `user.roles(pool)` loads the roles of a user with a subquery on
+//!   `user_roles`.
+//! * `user.attach_role(pool, role_id)` and `user.detach_role(pool, role_id)`
+//!   insert and delete a single row of the join table.
+//! * `user.sync_roles(pool, &role_ids)` replaces the rows of the user in the
+//!   join table with `role_ids` in a transaction.
+//!
+//! ```rust
+//! use sqlx::{FromRow, SqlitePool};
+//! use sqlx_crud::{Crud, Schema, SqlxCrud};
+//!
+//! #[derive(FromRow, SqlxCrud)]
+//! #[many_to_many(Role, through = "user_roles", local = "user_id", remote = "role_id")]
+//! struct User {
+//!     user_id: i64,
+//!     name: String,
+//! }
+//!
+//! #[derive(FromRow, SqlxCrud)]
+//! struct Role {
+//!     role_id: i64,
+//!     name: String,
+//! }
+//!
+//! # tokio_test::block_on(async {
+//! let pool = SqlitePool::connect(":memory:").await?;
+//! sqlx::query(User::create_table_sql()).execute(&pool).await?;
+//! sqlx::query(Role::create_table_sql()).execute(&pool).await?;
+//! sqlx::query("CREATE TABLE user_roles (user_id INTEGER, role_id INTEGER)")
+//!     .execute(&pool)
+//!     .await?;
+//!
+//! User { user_id: 1, name: "alice".to_string() }.create(&pool).await?;
+//! for (role_id, name) in [(1, "admin"), (2, "editor"), (3, "viewer")] {
+//!     Role { role_id, name: name.to_string() }.create(&pool).await?;
+//! }
+//!
+//! let alice = User::by_id(&pool, 1).await?.unwrap();
+//! alice.attach_role(&pool, 1).await?;
+//! alice.attach_role(&pool, 2).await?;
+//! alice.detach_role(&pool, 1).await?;
+//! assert_eq!("editor", alice.roles(&pool).await?[0].name);
+//!
+//! alice.sync_roles(&pool, &[1, 3]).await?;
+//! let roles = alice.roles(&pool).await?;
+//! assert_eq!(vec![1, 3], roles.iter().map(|r| r.role_id).collect::<Vec<_>>());
+//! # Ok::<(), sqlx::Error>(())
+//! # }).unwrap();
+//! ```
+
+use std::collections::HashSet;
+use std::hash::Hash;
+
+use sqlx::database::HasArguments;
+use sqlx::{Database, Encode, Executor, FromRow, IntoArguments, Pool, Type};
+
+use crate::schema::Dialect;
+use crate::traits::Schema;
+
+/// Foreign key fields referencing a record with an id of type `Id`, either
+/// `Id` itself or `Option<Id>`.
+#[doc(hidden)]
+pub trait ForeignKey<Id> {
+    fn key(&self) -> Option<Id>;
+}
+
+impl<Id: Clone> ForeignKey<Id> for Id {
+    fn key(&self) -> Option<Id> {
+        Some(self.clone())
+    }
+}
+
+impl<Id: Clone> ForeignKey<Id> for Option<Id> {
+    fn key(&self) -> Option<Id> {
+        self.clone()
+    }
+}
+
+/// Maximum number of values bound by a single `IN` query.
+const IN_CHUNK_SIZE: usize = 1000;
+
+/// Selects the records of `T` whose `column` is one of `values`, skipping
+/// soft deleted records. Large lists are split over several queries, and
+/// duplicate values are bound once so no record is returned twice.
+#[doc(hidden)]
+pub async fn select_in<DB, T, V>(
+    pool: &Pool<DB>,
+    column: &str,
+    values: Vec<V>,
+) -> Result<Vec<T>, sqlx::Error>
+where
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> <DB as HasArguments<'q>>::Arguments: IntoArguments<'q, DB>,
+    T: Schema + Send + Unpin + for<'r> FromRow<'r, DB::Row>,
+    V: Clone + Eq + Hash + Send + for<'q> Encode<'q, DB> + Type<DB>,
+{
+    if !T::columns().contains(&column) {
+        return Err(sqlx::Error::Configuration(
+            format!("{} has no column {}", T::table_name(), column).into(),
+        ));
+    }
+
+    let dialect = T::dialect();
+    let keyword = filter_keyword::<T>();
+    let column = format!(
+        "{}.{}",
+        dialect.quote_ident(T::table_name()),
+        dialect.quote_ident(column)
+    );
+
+    let mut seen = HashSet::new();
+    let values = values
+        .into_iter()
+        .filter(|value| seen.insert(value.clone()))
+        .collect::<Vec<_>>();
+
+    let mut records = Vec::new();
+    for chunk in values.chunks(IN_CHUNK_SIZE) {
+        let sql = format!(
+            "{} {} {} IN ({})",
+            T::select_sql(),
+            keyword,
+            column,
+            vec!["?"; chunk.len()].join(", ")
+        );
+        let mut query = sqlx::query_as::<DB, T>(&sql);
+        for value in chunk {
+            query = query.bind(value.clone());
+        }
+        records.extend(query.fetch_all(pool).await?);
+    }
+
+    Ok(records)
+}
+
+/// Returns the keyword adding a condition to the [select_sql](Schema::select_sql)
+/// of `T`, which already has a WHERE clause if it filters soft deleted rows.
+fn filter_keyword<T: Schema>() -> &'static str {
+    if T::select_sql() == T::select_with_deleted_sql() {
+        "WHERE"
+    } else {
+        "AND"
+    }
+}
+
+/// The join table of a many-to-many relationship, with the column `local`
+/// referencing the model declaring it and `remote` the related model.
+#[doc(hidden)]
+#[derive(Clone, Copy, Debug)]
+pub struct JoinTable {
+    pub dialect: Dialect,
+    pub table: &'static str,
+    pub local: &'static str,
+    pub remote: &'static str,
+}
+
+impl JoinTable {
+    /// Selects the records of `T` related to the record `local`, skipping
+    /// soft deleted records.
+    pub async fn select<DB, T, L>(self, pool: &Pool<DB>, local: L) -> Result<Vec<T>, sqlx::Error>
+    where
+        DB: Database,
+        for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+        for<'q> <DB as HasArguments<'q>>::Arguments: IntoArguments<'q, DB>,
+        T: Schema + Send + Unpin + for<'r> FromRow<'r, DB::Row>,
+        L: Send + for<'q> Encode<'q, DB> + Type<DB>,
+    {
+        let q = |ident| self.dialect.quote_ident(ident);
+        let sql = format!(
+            "{} {} {}.{} IN (SELECT {} FROM {} WHERE {} = ?)",
+            T::select_sql(),
+            filter_keyword::<T>(),
+            q(T::table_name()),
+            q(T::id_column()),
+            q(self.remote),
+            q(self.table),
+            q(self.local)
+        );
+
+        sqlx::query_as::<DB, T>(&sql)
+            .bind(local)
+            .fetch_all(pool)
+            .await
+    }
+
+    /// Relates the records `local` and `remote`.
+    pub async fn attach<DB, L, R>(
+        self,
+        pool: &Pool<DB>,
+        local: L,
+        remote: R,
+    ) -> Result<(), sqlx::Error>
+    where
+        DB: Database,
+        for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+        for<'q> <DB as HasArguments<'q>>::Arguments: IntoArguments<'q, DB>,
+        L: Send + for<'q> Encode<'q, DB> + Type<DB>,
+        R: Send + for<'q> Encode<'q, DB> + Type<DB>,
+    {
+        sqlx::query::<DB>(&self.insert_sql())
+            .bind(local)
+            .bind(remote)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Removes the relation between the records `local` and `remote`.
+    pub async fn detach<DB, L, R>(
+        self,
+        pool: &Pool<DB>,
+        local: L,
+        remote: R,
+    ) -> Result<(), sqlx::Error>
+    where
+        DB: Database,
+        for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+        for<'q> <DB as HasArguments<'q>>::Arguments: IntoArguments<'q, DB>,
+        L: Send + for<'q> Encode<'q, DB> + Type<DB>,
+        R: Send + for<'q> Encode<'q, DB> + Type<DB>,
+    {
+        let q = |ident| self.dialect.quote_ident(ident);
+        let sql = format!(
+            "DELETE FROM {} WHERE {} = ? AND {} = ?",
+            q(self.table),
+            q(self.local),
+            q(self.remote)
+        );
+
+        sqlx::query::<DB>(&sql)
+            .bind(local)
+            .bind(remote)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Relates the record `local` to exactly the records `remotes` in a
+    /// transaction.
+    pub async fn sync<DB, L, R>(
+        self,
+        pool: &Pool<DB>,
+        local: L,
+        remotes: &[R],
+    ) -> Result<(), sqlx::Error>
+    where
+        DB: Database,
+        for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+        for<'q> <DB as HasArguments<'q>>::Arguments: IntoArguments<'q, DB>,
+        L: Copy + Send + for<'q> Encode<'q, DB> + Type<DB>,
+        R: Copy + Send + Sync + for<'q> Encode<'q, DB> + Type<DB>,
+    {
+        let q = |ident| self.dialect.quote_ident(ident);
+        let delete_sql = format!("DELETE FROM {} WHERE {} = ?", q(self.table), q(self.local));
+        let insert_sql = self.insert_sql();
+
+        let mut tx = pool.begin().await?;
+        sqlx::query::<DB>(&delete_sql)
+            .bind(local)
+            .execute(&mut *tx)
+            .await?;
+        for remote in remotes {
+            sqlx::query::<DB>(&insert_sql)
+                .bind(local)
+                .bind(*remote)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await
+    }
+
+    fn insert_sql(self) -> String {
+        let q = |ident| self.dialect.quote_ident(ident);
+        format!(
+            "INSERT INTO {} ({}, {}) VALUES (?, ?)",
+            q(self.table),
+            q(self.local),
+            q(self.remote)
+        )
+    }
+}
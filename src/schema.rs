@@ -1,10 +1,150 @@
 pub struct Metadata<'s, const C: usize> {
+    pub dialect: Dialect,
     pub table_name: &'s str,
     pub id_column: &'s str,
     pub columns: [&'s str; C],
+    pub column_meta: [ColumnMeta<'s>; C],
+    pub indexes: &'s [IndexMeta<'s>],
+    pub audited: bool,
+    pub outbox: bool,
+    pub events: bool,
     pub select_sql: &'s str,
     pub select_by_id_sql: &'s str,
     pub insert_sql: &'s str,
     pub update_by_id_sql: &'s str,
     pub delete_by_id_sql: &'s str,
+    pub force_delete_by_id_sql: &'s str,
+    pub select_with_deleted_sql: &'s str,
+    pub not_deleted_sql: Option<&'s str>,
+    pub select_only_deleted_sql: Option<&'s str>,
+    pub restore_by_id_sql: Option<&'s str>,
+    pub purge_deleted_sql: Option<&'s str>,
+    pub delete_as_by_id_sql: Option<&'s str>,
+    pub soft_delete_sql: Option<&'s str>,
+    pub create_table_sql: &'s str,
+    pub drop_table_sql: &'s str,
+}
+
+/// The SQL dialect of the database a model was derived for. Selected with the
+/// `#[database(...)]` struct attribute.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Dialect {
+    Any,
+    Mssql,
+    MySql,
+    Postgres,
+    Sqlite,
+}
+
+impl Dialect {
+    /// Quotes an identifier, e.g. a table or column name.
+    pub fn quote_ident(&self, ident: &str) -> String {
+        match self {
+            Self::MySql => format!("`{}`", ident),
+            _ => format!(r#""{}""#, ident),
+        }
+    }
+
+    /// Returns the statement dropping the index `index` of `table`.
+    pub fn drop_index_sql(&self, table: &str, index: &str) -> String {
+        match self {
+            Self::MySql | Self::Mssql => format!(
+                "DROP INDEX {} ON {}",
+                self.quote_ident(index),
+                self.quote_ident(table)
+            ),
+            _ => format!("DROP INDEX {}", self.quote_ident(index)),
+        }
+    }
+
+    /// Returns the statement creating `table`, a log written by the crate
+    /// such as the audit log or the outbox, with the auto increment primary
+    /// key `id_column` followed by `columns`.
+    pub(crate) fn create_log_table_sql(
+        &self,
+        table: &str,
+        id_column: &str,
+        columns: &[(&str, LogColumn)],
+    ) -> String {
+        let (id, short_text, text) = match self {
+            Self::Any | Self::Sqlite => ("INTEGER PRIMARY KEY AUTOINCREMENT", "TEXT", "TEXT"),
+            Self::Postgres => ("BIGSERIAL PRIMARY KEY", "TEXT", "TEXT"),
+            Self::MySql => (
+                "BIGINT NOT NULL AUTO_INCREMENT PRIMARY KEY",
+                "VARCHAR(255)",
+                "LONGTEXT",
+            ),
+            Self::Mssql => (
+                "BIGINT NOT NULL IDENTITY(1, 1) PRIMARY KEY",
+                "NVARCHAR(255)",
+                "NVARCHAR(MAX)",
+            ),
+        };
+        let definitions = std::iter::once(format!("{} {}", self.quote_ident(id_column), id))
+            .chain(columns.iter().map(|(name, column)| {
+                let name = self.quote_ident(name);
+                match column {
+                    LogColumn::Text => format!("{} {} NOT NULL", name, short_text),
+                    LogColumn::OptionalText => format!("{} {}", name, short_text),
+                    LogColumn::Document => format!("{} {} NOT NULL", name, text),
+                    LogColumn::OptionalDocument => format!("{} {}", name, text),
+                }
+            }))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        format!(
+            "CREATE TABLE IF NOT EXISTS {} ({})",
+            self.quote_ident(table),
+            definitions
+        )
+    }
+}
+
+/// Type of a column of a table created with [Dialect::create_log_table_sql].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum LogColumn {
+    /// A short string such as a name, an id or a timestamp.
+    Text,
+    OptionalText,
+    /// An unbounded string such as a JSON document.
+    Document,
+    OptionalDocument,
+}
+
+/// Describes a single column of a table as derived from the struct field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ColumnMeta<'s> {
+    /// Database name of the column.
+    pub name: &'s str,
+    /// The Rust type of the field as written in the struct, e.g. `Option<String>`.
+    pub rust_type: &'s str,
+    /// The column type used by `create_table_sql` for the model's database.
+    pub sql_type: &'s str,
+    /// The column definition used by `create_table_sql`, e.g.
+    /// `"name" TEXT NOT NULL`.
+    pub definition: &'s str,
+    /// `true` when the field is an `Option<T>`.
+    pub nullable: bool,
+    /// `true` for the primary key column.
+    pub is_id: bool,
+    /// `true` when the primary key is tagged with `#[auto_increment]`.
+    pub auto_increment: bool,
+    /// `true` when the column is bound by `insert_sql`.
+    pub insertable: bool,
+    /// `true` when the column is bound by `update_by_id_sql`.
+    pub updatable: bool,
+    /// `true` for the `#[deleted_with]` soft delete marker column.
+    pub soft_delete: bool,
+}
+
+/// An index declared with the `#[index]` or `#[index(unique)]` field
+/// attribute. Indexes are named `idx_<table>_<column>`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IndexMeta<'s> {
+    pub name: &'s str,
+    pub column: &'s str,
+    pub unique: bool,
+    /// The `CREATE INDEX` statement for the model's database.
+    pub create_sql: &'s str,
 }
@@ -0,0 +1,459 @@
+//! SELECT queries joining several models.
+//!
+//! [Select] starts from the columns of one model and adds the columns of
+//! every joined model, each aliased as `table__column` so the result has no
+//! ambiguous names. Rows decode into tuples of the models in join order.
+//! Joined models usually share column names like `user_id`, so a row is
+//! decoded by column position, with a decoder generated by
+//! [SqlxCrud](crate::SqlxCrud) from the same columns as
+//! [select_sql](crate::Schema::select_sql). It reads the `rename`,
+//! `try_from` and `default` field attributes of `#[sqlx(...)]` like
+//! [FromRow](sqlx::FromRow), `flatten` fields aren't supported. Soft deleted
+//! records of any of the models are skipped.
+//!
+//! The `on` condition of a join is plain SQL. A model can only be joined
+//! once, there is no aliasing of tables.
+//!
+//! # Joins
+//!
+//! ```rust
+//! use sqlx::{FromRow, SqlitePool};
+//! use sqlx_crud::select::Select;
+//! use sqlx_crud::{Crud, Schema, SqlxCrud};
+//!
+//! #[derive(FromRow, SqlxCrud)]
+//! struct User {
+//!     user_id: i64,
+//!     name: String,
+//! }
+//!
+//! #[derive(FromRow, SqlxCrud)]
+//! struct Post {
+//!     post_id: i64,
+//!     user_id: i64,
+//!     title: String,
+//! }
+//!
+//! # tokio_test::block_on(async {
+//! let pool = SqlitePool::connect(":memory:").await?;
+//! sqlx::query(User::create_table_sql()).execute(&pool).await?;
+//! sqlx::query(Post::create_table_sql()).execute(&pool).await?;
+//!
+//! User { user_id: 1, name: "alice".to_string() }.create(&pool).await?;
+//! Post { post_id: 1, user_id: 1, title: "Hello".to_string() }.create(&pool).await?;
+//!
+//! let select = Select::<Post>::new().join::<User>("posts.user_id = users.user_id");
+//! assert_eq!(
+//!     concat!(
+//!         r#"SELECT "posts"."post_id" AS "posts__post_id", "posts"."user_id" AS "posts__user_id", "#,
+//!         r#""posts"."title" AS "posts__title", "users"."user_id" AS "users__user_id", "#,
+//!         r#""users"."name" AS "users__name" FROM "posts" "#,
+//!         r#"JOIN "users" ON posts.user_id = users.user_id"#,
+//!     ),
+//!     select.sql()
+//! );
+//!
+//! let (post, user) = select.fetch_all(&pool).await?.remove(0);
+//! assert_eq!(("Hello", "alice"), (post.title.as_str(), user.name.as_str()));
+//! # Ok::<(), sqlx::Error>(())
+//! # }).unwrap();
+//! ```
+//!
+//! # Sorting and paging
+//!
+//! [Crud::query](crate::Crud::query) returns a [Select] of one model.
+//! [order_by](Select::order_by) takes the typed columns returned by the
+//! generated `Model::cols()`, and [Sort::parse] turns a client supplied list
+//! like `name,-created_at` into an order, rejecting anything that isn't a
+//! column of the model.
+//!
+//! ```rust
+//! use sqlx::{FromRow, SqlitePool};
+//! use sqlx_crud::select::Sort;
+//! use sqlx_crud::{Crud, Schema, SqlxCrud};
+//!
+//! #[derive(FromRow, SqlxCrud)]
+//! struct Task {
+//!     task_id: i64,
+//!     name: String,
+//!     created_at: i64,
+//! }
+//!
+//! # tokio_test::block_on(async {
+//! let pool = SqlitePool::connect(":memory:").await?;
+//! sqlx::query(Task::create_table_sql()).execute(&pool).await?;
+//!
+//! for (task_id, name, created_at) in [(1, "b", 10), (2, "a", 20), (3, "b", 30)] {
+//!     Task { task_id, name: name.to_string(), created_at }.create(&pool).await?;
+//! }
+//!
+//! let newest = Task::query()
+//!     .order_by(Task::cols().created_at.desc())
+//!     .limit(2)
+//!     .fetch_all(&pool)
+//!     .await?;
+//! assert_eq!(vec![3, 2], newest.iter().map(|t| t.task_id).collect::<Vec<_>>());
+//!
+//! let sort = Sort::parse("name,-created_at").unwrap();
+//! let tasks = Task::query().sort(sort).offset(1).fetch_all(&pool).await?;
+//! assert_eq!(vec![3, 1], tasks.iter().map(|t| t.task_id).collect::<Vec<_>>());
+//!
+//! assert!(Sort::<Task>::parse("name; DROP TABLE tasks").is_err());
+//! # Ok::<(), sqlx::Error>(())
+//! # }).unwrap();
+//! ```
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use sqlx::database::HasArguments;
+use sqlx::{Database, Executor, IntoArguments, Pool, Row};
+
+use crate::schema::Dialect;
+use crate::traits::Schema;
+
+/// Decodes a model from the columns of a joined row starting at `offset`.
+/// Implemented by [SqlxCrud](crate::SqlxCrud) and for tuples of models.
+#[doc(hidden)]
+pub trait FromJoinRow<R: Row>: Sized {
+    fn from_join_row(row: &R, offset: usize) -> Result<Self, sqlx::Error>;
+}
+
+impl<R, A, B> FromJoinRow<R> for (A, B)
+where
+    R: Row,
+    A: Schema + FromJoinRow<R>,
+    B: Schema + FromJoinRow<R>,
+{
+    fn from_join_row(row: &R, offset: usize) -> Result<Self, sqlx::Error> {
+        let a = A::from_join_row(row, offset)?;
+        let b = B::from_join_row(row, offset + A::columns().len())?;
+        Ok((a, b))
+    }
+}
+
+impl<R, A, B, C> FromJoinRow<R> for (A, B, C)
+where
+    R: Row,
+    A: Schema + FromJoinRow<R>,
+    B: Schema + FromJoinRow<R>,
+    C: Schema + FromJoinRow<R>,
+{
+    fn from_join_row(row: &R, offset: usize) -> Result<Self, sqlx::Error> {
+        let (a, b) = <(A, B)>::from_join_row(row, offset)?;
+        let c = C::from_join_row(row, offset + A::columns().len() + B::columns().len())?;
+        Ok((a, b, c))
+    }
+}
+
+/// A column of the model `T` holding values of type `V`.
+pub struct Column<T, V> {
+    name: &'static str,
+    model: PhantomData<fn() -> (T, V)>,
+}
+
+impl<T, V> Column<T, V> {
+    #[doc(hidden)]
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            model: PhantomData,
+        }
+    }
+
+    /// Returns the name of the column.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+impl<T: Schema, V> Column<T, V> {
+    /// Sorts by the column in ascending order.
+    pub fn asc(self) -> Order {
+        Order::new::<T>(self.name, false)
+    }
+
+    /// Sorts by the column in descending order.
+    pub fn desc(self) -> Order {
+        Order::new::<T>(self.name, true)
+    }
+}
+
+impl<T, V> Clone for Column<T, V> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, V> Copy for Column<T, V> {}
+
+impl<T, V> fmt::Debug for Column<T, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Column").field(&self.name).finish()
+    }
+}
+
+/// A column of a [Select] sorted in ascending or descending order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Order {
+    table: &'static str,
+    column: &'static str,
+    descending: bool,
+}
+
+impl Order {
+    fn new<T: Schema>(column: &'static str, descending: bool) -> Self {
+        Self {
+            table: T::table_name(),
+            column,
+            descending,
+        }
+    }
+}
+
+/// A list of columns of `T` to sort by, usually supplied by a client.
+pub struct Sort<T> {
+    orders: Vec<Order>,
+    model: PhantomData<fn() -> T>,
+}
+
+impl<T: Schema> Sort<T> {
+    /// Parses a comma separated list of column names, each sorted in
+    /// descending order if prefixed with `-`. Fails on names that aren't
+    /// columns of `T`, so the list can't inject SQL.
+    pub fn parse(sort: &str) -> Result<Self, SortError> {
+        let orders = sort
+            .split(',')
+            .map(|field| {
+                let field = field.trim();
+                let (name, descending) = match field.strip_prefix('-') {
+                    Some(name) => (name, true),
+                    None => (field, false),
+                };
+                T::columns()
+                    .iter()
+                    .find(|column| **column == name)
+                    .map(|column| Order::new::<T>(column, descending))
+                    .ok_or_else(|| SortError {
+                        table: T::table_name(),
+                        field: field.to_string(),
+                    })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            orders,
+            model: PhantomData,
+        })
+    }
+
+    /// Returns the orders of the sort.
+    pub fn orders(&self) -> &[Order] {
+        &self.orders
+    }
+}
+
+impl<T> fmt::Debug for Sort<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Sort").field(&self.orders).finish()
+    }
+}
+
+/// Error returned by [Sort::parse] for a field which isn't a column.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+#[error("{table} can't be sorted by {field:?}")]
+pub struct SortError {
+    pub table: &'static str,
+    pub field: String,
+}
+
+/// A SELECT query returning rows of `S`, a model or a tuple of joined
+/// models.
+pub struct Select<S> {
+    dialect: Dialect,
+    from: &'static str,
+    columns: Vec<String>,
+    joins: Vec<String>,
+    conditions: Vec<&'static str>,
+    orders: Vec<Order>,
+    limit: Option<u64>,
+    offset: Option<u64>,
+    rows: PhantomData<fn() -> S>,
+}
+
+impl<T: Schema> Select<T> {
+    /// Returns a query selecting the records of `T`.
+    pub fn new() -> Self {
+        let mut select = Self {
+            dialect: T::dialect(),
+            from: T::table_name(),
+            columns: Vec::new(),
+            joins: Vec::new(),
+            conditions: Vec::new(),
+            orders: Vec::new(),
+            limit: None,
+            offset: None,
+            rows: PhantomData,
+        };
+        select.add::<T>();
+        select
+    }
+
+    /// Sorts the records by `sort`, after any previous order.
+    pub fn sort(mut self, sort: Sort<T>) -> Self {
+        self.orders.extend(sort.orders);
+        self
+    }
+
+    /// Joins the records of `U` matching the condition `on`.
+    pub fn join<U: Schema>(self, on: &str) -> Select<(T, U)> {
+        self.join_with::<U, _>(on)
+    }
+}
+
+impl<T: Schema> Default for Select<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Schema, U: Schema> Select<(T, U)> {
+    /// Joins the records of `V` matching the condition `on`.
+    pub fn join<V: Schema>(self, on: &str) -> Select<(T, U, V)> {
+        self.join_with::<V, _>(on)
+    }
+}
+
+impl<S> Select<S> {
+    /// Sorts the rows by `order`, after any previous order.
+    pub fn order_by(mut self, order: Order) -> Self {
+        self.orders.push(order);
+        self
+    }
+
+    /// Returns at most `limit` rows.
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Skips the first `offset` rows.
+    pub fn offset(mut self, offset: u64) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Returns the SQL of the query.
+    pub fn sql(&self) -> String {
+        let mut sql = format!(
+            "SELECT {} FROM {}",
+            self.columns.join(", "),
+            self.dialect.quote_ident(self.from)
+        );
+        for join in &self.joins {
+            sql.push(' ');
+            sql.push_str(join);
+        }
+        if !self.conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&self.conditions.join(" AND "));
+        }
+        self.push_order(&mut sql);
+        self.push_limit(&mut sql);
+        sql
+    }
+
+    /// Runs the query and decodes every row.
+    pub async fn fetch_all<DB>(&self, pool: &Pool<DB>) -> Result<Vec<S>, sqlx::Error>
+    where
+        DB: Database,
+        for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+        for<'q> <DB as HasArguments<'q>>::Arguments: IntoArguments<'q, DB>,
+        S: FromJoinRow<DB::Row> + Send + Unpin,
+    {
+        sqlx::query::<DB>(&self.sql())
+            .try_map(|row: DB::Row| S::from_join_row(&row, 0))
+            .fetch_all(pool)
+            .await
+    }
+
+    fn push_order(&self, sql: &mut String) {
+        let q = |ident| self.dialect.quote_ident(ident);
+        let orders = self
+            .orders
+            .iter()
+            .map(|order| {
+                let direction = if order.descending { "DESC" } else { "ASC" };
+                format!("{}.{} {}", q(order.table), q(order.column), direction)
+            })
+            .collect::<Vec<_>>();
+
+        if !orders.is_empty() {
+            sql.push_str(" ORDER BY ");
+            sql.push_str(&orders.join(", "));
+        } else if self.dialect == Dialect::Mssql && (self.limit.is_some() || self.offset.is_some())
+        {
+            // OFFSET ... FETCH requires an ORDER BY clause.
+            sql.push_str(" ORDER BY (SELECT NULL)");
+        }
+    }
+
+    fn push_limit(&self, sql: &mut String) {
+        match (self.dialect, self.limit, self.offset) {
+            (_, None, None) => {}
+            (Dialect::Mssql, limit, offset) => {
+                sql.push_str(&format!(" OFFSET {} ROWS", offset.unwrap_or(0)));
+                if let Some(limit) = limit {
+                    sql.push_str(&format!(" FETCH NEXT {} ROWS ONLY", limit));
+                }
+            }
+            (dialect, limit, offset) => {
+                // SQLite and MySQL only accept OFFSET after a LIMIT.
+                match (limit, dialect) {
+                    (Some(limit), _) => sql.push_str(&format!(" LIMIT {}", limit)),
+                    (None, Dialect::MySql) => sql.push_str(&format!(" LIMIT {}", u64::MAX)),
+                    (None, Dialect::Any | Dialect::Sqlite) => sql.push_str(" LIMIT -1"),
+                    (None, _) => {}
+                }
+                if let Some(offset) = offset {
+                    sql.push_str(&format!(" OFFSET {}", offset));
+                }
+            }
+        }
+    }
+
+    /// Adds the columns and the soft delete condition of `U`.
+    fn add<U: Schema>(&mut self) {
+        let table = U::table_name();
+        let dialect = self.dialect;
+        self.columns.extend(U::columns().iter().map(|column| {
+            format!(
+                "{}.{} AS {}",
+                dialect.quote_ident(table),
+                dialect.quote_ident(column),
+                dialect.quote_ident(&format!("{}__{}", table, column))
+            )
+        }));
+        self.conditions.extend(U::not_deleted_sql());
+    }
+
+    fn join_with<U: Schema, N>(mut self, on: &str) -> Select<N> {
+        self.joins.push(format!(
+            "JOIN {} ON {}",
+            self.dialect.quote_ident(U::table_name()),
+            on
+        ));
+        self.add::<U>();
+        Select {
+            dialect: self.dialect,
+            from: self.from,
+            columns: self.columns,
+            joins: self.joins,
+            conditions: self.conditions,
+            orders: self.orders,
+            limit: self.limit,
+            offset: self.offset,
+            rows: PhantomData,
+        }
+    }
+}
@@ -0,0 +1,176 @@
+//! The current time bound for `#[created_at]` and `#[updated_at]` fields.
+//!
+//! A field tagged with `#[created_at]` is set to [Now::now] by
+//! [insert_args](crate::Crud::insert_args) and left out of updates. A field
+//! tagged with `#[updated_at]` is set by both
+//! [insert_args](crate::Crud::insert_args) and
+//! [update_args](crate::Crud::update_args). The time is taken by the
+//! application instead of a database default or trigger, so timestamps behave
+//! the same on every database.
+//!
+//! [Now] is implemented for the chrono types with the `chrono` feature, the
+//! time types with the `time` feature and `Option<T>` of those.
+//!
+//! # Example
+//!
+//! ```rust
+//! # #[cfg(feature = "chrono")] {
+//! use sqlx::types::chrono::{DateTime, Utc};
+//! use sqlx::{FromRow, SqlitePool};
+//! use sqlx_crud::{Crud, Schema, SqlxCrud};
+//!
+//! #[derive(FromRow, SqlxCrud)]
+//! struct Post {
+//!     #[id]
+//!     #[auto_increment]
+//!     post_id: i64,
+//!     title: String,
+//!     #[created_at]
+//!     created_at: Option<DateTime<Utc>>,
+//!     #[updated_at]
+//!     updated_at: Option<DateTime<Utc>>,
+//! }
+//!
+//! # tokio_test::block_on(async {
+//! let pool = SqlitePool::connect(":memory:").await?;
+//! sqlx::query(Post::create_table_sql()).execute(&pool).await?;
+//!
+//! let post = Post { post_id: 0, title: "hello".to_string(), created_at: None, updated_at: None };
+//! post.create(&pool).await?;
+//!
+//! let mut post = Post::by_id(&pool, 1).await?.unwrap();
+//! let created_at = post.created_at;
+//! assert!(created_at.is_some());
+//!
+//! post.title = "world".to_string();
+//! post.update(&pool).await?;
+//! let post = Post::by_id(&pool, 1).await?.unwrap();
+//! assert_eq!(created_at, post.created_at);
+//! assert!(post.updated_at >= created_at);
+//! # Ok::<(), sqlx::Error>(())
+//! # }).unwrap();
+//! # }
+//! ```
+//!
+//! # The timestamps attribute
+//!
+//! The [timestamps](crate::timestamps) attribute adds `created_at`,
+//! `updated_at` and `deleted_at` fields of type `Option<DateTime<Local>>` to
+//! a struct. `deleted_at` soft deletes records with `#[deleted_with(now)]`.
+//! The attribute has to be placed before `#[derive(SqlxCrud)]`. The field
+//! names can be changed, or a field left out with `false`, and `ty` sets the
+//! type wrapped in `Option`:
+//!
+//! ```rust
+//! # #[cfg(feature = "chrono")] {
+//! use sqlx::FromRow;
+//! use sqlx_crud::{timestamps, Schema, SqlxCrud};
+//!
+//! #[timestamps]
+//! #[derive(FromRow, SqlxCrud)]
+//! struct Post {
+//!     post_id: i64,
+//!     title: String,
+//! }
+//!
+//! #[timestamps(created_at = "inserted_at", deleted_at = false, ty = "sqlx::types::chrono::NaiveDateTime")]
+//! #[derive(FromRow, SqlxCrud)]
+//! struct Comment {
+//!     comment_id: i64,
+//!     body: String,
+//! }
+//!
+//! assert_eq!(&["post_id", "title", "created_at", "updated_at", "deleted_at"], Post::columns());
+//! assert!(Post::column_meta()[4].soft_delete);
+//! assert_eq!(&["comment_id", "body", "inserted_at", "updated_at"], Comment::columns());
+//! assert_eq!("Option<sqlx::types::chrono::NaiveDateTime>", Comment::column_meta()[3].rust_type);
+//! # }
+//! ```
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Types with a current time value.
+pub trait Now {
+    /// Returns the current time.
+    fn now() -> Self;
+}
+
+impl<T: Now> Now for Option<T> {
+    fn now() -> Self {
+        Some(T::now())
+    }
+}
+
+/// Splits `time` into the UTC year, month, day, hour, minute and second.
+pub(crate) fn utc(time: SystemTime) -> [i64; 6] {
+    let secs = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()) as i64;
+    let (days, secs) = (secs / 86_400, secs % 86_400);
+
+    // Civil from days, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    [year, month, day, secs / 3_600, secs % 3_600 / 60, secs % 60]
+}
+
+/// Formats `time` as `YYYY-MM-DD HH:MM:SS` in UTC.
+pub(crate) fn format_utc(time: SystemTime) -> String {
+    let [year, month, day, hour, minute, second] = utc(time);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year, month, day, hour, minute, second
+    )
+}
+
+#[cfg(feature = "chrono")]
+mod chrono_impls {
+    use sqlx::types::chrono::{DateTime, Local, NaiveDateTime, Utc};
+
+    use super::Now;
+
+    impl Now for DateTime<Utc> {
+        fn now() -> Self {
+            Utc::now()
+        }
+    }
+
+    impl Now for DateTime<Local> {
+        fn now() -> Self {
+            Local::now()
+        }
+    }
+
+    impl Now for NaiveDateTime {
+        fn now() -> Self {
+            Utc::now().naive_utc()
+        }
+    }
+}
+
+#[cfg(feature = "time")]
+mod time_impls {
+    use sqlx::types::time::{OffsetDateTime, PrimitiveDateTime};
+
+    use super::Now;
+
+    impl Now for OffsetDateTime {
+        fn now() -> Self {
+            OffsetDateTime::now_utc()
+        }
+    }
+
+    impl Now for PrimitiveDateTime {
+        fn now() -> Self {
+            let now = OffsetDateTime::now_utc();
+            PrimitiveDateTime::new(now.date(), now.time())
+        }
+    }
+}
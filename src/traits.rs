@@ -1,11 +1,20 @@
+use std::fmt;
 use std::pin::Pin;
+use std::str::FromStr;
 
 use futures::stream::Stream;
 use futures::stream::TryCollect;
 use futures::Future;
-use futures::TryStreamExt;
+use futures::{TryFutureExt, TryStreamExt};
 use sqlx::database::HasArguments;
-use sqlx::{Database, Encode, Executor, FromRow, IntoArguments, Type};
+use sqlx::query::Query;
+use sqlx::{Database, Encode, Executor, FromRow, IntoArguments, Transaction, Type};
+
+use crate::filter::{self, Filter, Patch};
+use crate::lock::{self, Lock};
+use crate::schema::{ColumnMeta, Dialect, IndexMeta};
+use crate::select::{Column, Select};
+use crate::validate::Validate;
 
 /// Type alias for methods returning a single element. The future resolves to and
 /// `Result<T, sqlx::Error>`.
@@ -18,6 +27,45 @@ pub type CrudStream<'e, T> =
 /// Type alias for a [`TryCollect`] future that resolves to `Result<Vec<T>, sqlx::Error>`.
 pub type TryCollectFut<'e, T> = TryCollect<CrudStream<'e, T>, Vec<T>>;
 
+/// The kind of write done by a [Crud] method, as recorded in the
+/// [audit log](crate::audit) and the [outbox](crate::outbox).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operation {
+    Create,
+    Update,
+    Delete,
+}
+
+impl Operation {
+    /// Returns the name stored in the `operation` columns.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Create => "create",
+            Self::Update => "update",
+            Self::Delete => "delete",
+        }
+    }
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Operation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "create" => Ok(Self::Create),
+            "update" => Ok(Self::Update),
+            "delete" => Ok(Self::Delete),
+            _ => Err(format!("unknown operation {}", s)),
+        }
+    }
+}
+
 /// Database schema information about a struct implementing sqlx [FromRow].
 /// [Schema] defines methods for accessing the derived database schema
 /// and query information.
@@ -42,9 +90,13 @@ pub trait Schema {
     /// Type of the table primary key column.
     type Id: Copy + Send + Sync;
 
+    /// The dialect of the database selected with `#[database(...)]`.
+    fn dialect() -> Dialect;
+
     /// Database name of the table. Used by the query generation code and
     /// available for introspection. This is generated by taking the plural
     /// _snake_case_ of the struct's name. See: [Inflector to_table_case].
+    /// The `#[table_name = "..."]` attribute overrides the generated name.
     ///
     /// ```rust
     /// use sqlx::FromRow;
@@ -56,7 +108,15 @@ pub trait Schema {
     ///     audience: String,
     /// }
     ///
+    /// #[derive(FromRow, SqlxCrud)]
+    /// #[table_name = "user_role"]
+    /// struct UserRole {
+    ///     id: i32,
+    ///     role: String,
+    /// }
+    ///
     /// assert_eq!("google_id_tokens", GoogleIdToken::table_name());
+    /// assert_eq!("user_role", UserRole::table_name());
     /// ```
     ///
     /// [Inflector to_table_case]: https://docs.rs/Inflector/latest/inflector/cases/tablecase/fn.to_table_case.html
@@ -71,6 +131,76 @@ pub trait Schema {
     /// Returns an array of column names.
     fn columns() -> &'static [&'static str];
 
+    /// Returns a [ColumnMeta] for every column in the same order as
+    /// [columns](Schema::columns). Useful for introspecting the model
+    /// without access to its source.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sqlx::FromRow;
+    /// use sqlx_crud::{Schema, SqlxCrud};
+    ///
+    /// #[derive(FromRow, SqlxCrud)]
+    /// struct Post {
+    ///     #[id]
+    ///     #[auto_increment]
+    ///     post_id: i64,
+    ///     title: String,
+    ///     #[ignore_when(insert, update)]
+    ///     deleted_at: Option<String>,
+    /// }
+    ///
+    /// let columns = Post::column_meta();
+    /// assert!(columns[0].is_id && columns[0].auto_increment && !columns[0].insertable);
+    /// assert_eq!("String", columns[1].rust_type);
+    /// assert!(columns[1].insertable && columns[1].updatable);
+    /// assert_eq!("Option<String>", columns[2].rust_type);
+    /// assert!(columns[2].nullable && !columns[2].updatable);
+    /// ```
+    ///
+    /// [ColumnMeta]: crate::schema::ColumnMeta
+    fn column_meta() -> &'static [ColumnMeta<'static>];
+
+    /// Returns an [IndexMeta] for every index declared with the `#[index]` or
+    /// `#[index(unique)]` field attributes.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sqlx::FromRow;
+    /// use sqlx_crud::{Schema, SqlxCrud};
+    ///
+    /// #[derive(FromRow, SqlxCrud)]
+    /// struct Account {
+    ///     account_id: i64,
+    ///     #[index(unique)]
+    ///     email: String,
+    /// }
+    ///
+    /// let index = &Account::indexes()[0];
+    /// assert_eq!("idx_accounts_email", index.name);
+    /// assert_eq!(
+    ///     r#"CREATE UNIQUE INDEX "idx_accounts_email" ON "accounts" ("email")"#,
+    ///     index.create_sql
+    /// );
+    /// ```
+    ///
+    /// [IndexMeta]: crate::schema::IndexMeta
+    fn indexes() -> &'static [IndexMeta<'static>];
+
+    /// Returns `true` when the model is tagged with `#[crud(audited)]` and its
+    /// changes are recorded in the [audit log](crate::audit).
+    fn audited() -> bool;
+
+    /// Returns `true` when the model is tagged with `#[crud(outbox)]` and its
+    /// changes are inserted into the [outbox](crate::outbox).
+    fn outbox() -> bool;
+
+    /// Returns `true` when the model is tagged with `#[crud(events)]` and its
+    /// changes are published to the [events](crate::events) subscribers.
+    fn events() -> bool;
+
     /// Returns the SQL string for a SELECT query against the table.
     ///
     /// # Example
@@ -151,6 +281,378 @@ pub trait Schema {
     /// # }}
     /// ```
     fn delete_by_id_sql() -> &'static str;
+
+    /// Returns the SQL for permanently deleting a record by ID, even when the
+    /// model is soft deleted with `#[deleted_with]`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # sqlx_crud::doctest_setup! { |pool| {
+    /// use sqlx_crud::Schema;
+    ///
+    /// assert_eq!(r#"DELETE FROM "users" WHERE "users"."user_id" = ?"#, User::force_delete_by_id_sql());
+    /// # }}
+    /// ```
+    fn force_delete_by_id_sql() -> &'static str;
+
+    /// Returns the SQL string for a SELECT query against the table including
+    /// soft deleted records. This is the same as
+    /// [select_sql](Schema::select_sql) for models without `#[deleted_with]`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sqlx::FromRow;
+    /// use sqlx_crud::{Schema, SqlxCrud};
+    ///
+    /// #[derive(FromRow, SqlxCrud)]
+    /// struct Note {
+    ///     note_id: i64,
+    ///     #[deleted_with = "CURRENT_TIMESTAMP"]
+    ///     deleted_at: Option<String>,
+    /// }
+    ///
+    /// assert_eq!(
+    ///     r#"SELECT "notes"."note_id", "notes"."deleted_at" FROM "notes" WHERE "deleted_at" IS NULL"#,
+    ///     Note::select_sql()
+    /// );
+    /// assert_eq!(
+    ///     r#"SELECT "notes"."note_id", "notes"."deleted_at" FROM "notes""#,
+    ///     Note::select_with_deleted_sql()
+    /// );
+    /// ```
+    fn select_with_deleted_sql() -> &'static str;
+
+    /// Returns the condition matching the records which aren't soft deleted,
+    /// qualified with the table name so it can be used in joins, or `None`
+    /// if the model has no `#[deleted_with]` field.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sqlx::FromRow;
+    /// use sqlx_crud::{Schema, SqlxCrud};
+    ///
+    /// #[derive(FromRow, SqlxCrud)]
+    /// struct Note {
+    ///     note_id: i64,
+    ///     #[deleted_with = "CURRENT_TIMESTAMP"]
+    ///     deleted_at: Option<String>,
+    /// }
+    ///
+    /// assert_eq!(Some(r#""notes"."deleted_at" IS NULL"#), Note::not_deleted_sql());
+    /// ```
+    fn not_deleted_sql() -> Option<&'static str>;
+
+    /// Returns the SQL string for a SELECT query returning only soft deleted
+    /// records, or `None` if the model has no `#[deleted_with]` field.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sqlx::FromRow;
+    /// use sqlx_crud::{Schema, SqlxCrud};
+    ///
+    /// #[derive(FromRow, SqlxCrud)]
+    /// struct Note {
+    ///     note_id: i64,
+    ///     #[deleted_with = "CURRENT_TIMESTAMP"]
+    ///     deleted_at: Option<String>,
+    /// }
+    ///
+    /// assert_eq!(
+    ///     Some(r#"SELECT "notes"."note_id", "notes"."deleted_at" FROM "notes" WHERE "deleted_at" IS NOT NULL"#),
+    ///     Note::select_only_deleted_sql()
+    /// );
+    /// ```
+    fn select_only_deleted_sql() -> Option<&'static str>;
+
+    /// Returns the SQL for restoring a soft deleted record by ID, or `None` if
+    /// the model has no `#[deleted_with]` field.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sqlx::FromRow;
+    /// use sqlx_crud::{Schema, SqlxCrud};
+    ///
+    /// #[derive(FromRow, SqlxCrud)]
+    /// struct Note {
+    ///     note_id: i64,
+    ///     #[deleted_with = "CURRENT_TIMESTAMP"]
+    ///     deleted_at: Option<String>,
+    /// }
+    ///
+    /// assert_eq!(
+    ///     Some(r#"UPDATE "notes" SET "deleted_at" = NULL WHERE "notes"."note_id" = ? AND "deleted_at" IS NOT NULL"#),
+    ///     Note::restore_by_id_sql()
+    /// );
+    /// ```
+    fn restore_by_id_sql() -> Option<&'static str>;
+
+    /// Returns the SQL for permanently deleting records soft deleted before
+    /// a point in time, or `None` if the model has no `#[deleted_with]` field.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sqlx::FromRow;
+    /// use sqlx_crud::{Schema, SqlxCrud};
+    ///
+    /// #[derive(FromRow, SqlxCrud)]
+    /// struct Note {
+    ///     note_id: i64,
+    ///     #[deleted_with = "CURRENT_TIMESTAMP"]
+    ///     deleted_at: Option<String>,
+    /// }
+    ///
+    /// assert_eq!(
+    ///     Some(r#"DELETE FROM "notes" WHERE "deleted_at" IS NOT NULL AND "deleted_at" < ?"#),
+    ///     Note::purge_deleted_sql()
+    /// );
+    /// ```
+    fn purge_deleted_sql() -> Option<&'static str>;
+
+    /// Returns the SQL for soft deleting a record by ID and recording who
+    /// deleted it in the `#[deleted_by]` field, or `None` if the model has no
+    /// `#[deleted_by]` field. The first parameter is the actor, the second
+    /// the ID.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sqlx::FromRow;
+    /// use sqlx_crud::{Schema, SqlxCrud};
+    ///
+    /// #[derive(FromRow, SqlxCrud)]
+    /// struct Note {
+    ///     note_id: i64,
+    ///     #[deleted_with(flag)]
+    ///     is_deleted: bool,
+    ///     #[deleted_by]
+    ///     deleted_by: Option<i64>,
+    /// }
+    ///
+    /// assert_eq!(
+    ///     Some(r#"UPDATE "notes" SET "is_deleted" = TRUE, "deleted_by" = ? WHERE "notes"."note_id" = ? AND "is_deleted" = FALSE"#),
+    ///     Note::delete_as_by_id_sql()
+    /// );
+    /// assert_eq!(
+    ///     Some(r#"UPDATE "notes" SET "is_deleted" = FALSE, "deleted_by" = NULL WHERE "notes"."note_id" = ? AND "is_deleted" = TRUE"#),
+    ///     Note::restore_by_id_sql()
+    /// );
+    /// ```
+    fn delete_as_by_id_sql() -> Option<&'static str>;
+
+    /// Returns the SQL for soft deleting records without its WHERE clause,
+    /// which [Crud::delete_where] appends, or `None` if the model has no
+    /// `#[deleted_with]` field.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sqlx::FromRow;
+    /// use sqlx_crud::{Schema, SqlxCrud};
+    ///
+    /// #[derive(FromRow, SqlxCrud)]
+    /// struct Note {
+    ///     note_id: i64,
+    ///     #[deleted_with = "CURRENT_TIMESTAMP"]
+    ///     deleted_at: Option<String>,
+    /// }
+    ///
+    /// assert_eq!(
+    ///     Some(r#"UPDATE "notes" SET "deleted_at" = CURRENT_TIMESTAMP"#),
+    ///     Note::soft_delete_sql()
+    /// );
+    /// ```
+    fn soft_delete_sql() -> Option<&'static str>;
+
+    /// Returns the DDL creating the table in the model's database. Field
+    /// types are mapped to column types, `Option<T>` fields are `NULL`able and
+    /// the `#[id]` column is the primary key. Timestamp columns tagged
+    /// `#[ignore_when(insert)]`, which the database fills in, default to
+    /// `CURRENT_TIMESTAMP`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sqlx::{FromRow, SqlitePool};
+    /// use sqlx_crud::{Crud, Schema, SqlxCrud};
+    ///
+    /// #[derive(FromRow, SqlxCrud)]
+    /// struct Post {
+    ///     #[id]
+    ///     #[auto_increment]
+    ///     post_id: i64,
+    ///     title: String,
+    ///     body: Option<String>,
+    /// }
+    ///
+    /// assert_eq!(
+    ///     r#"CREATE TABLE IF NOT EXISTS "posts" ("post_id" INTEGER PRIMARY KEY AUTOINCREMENT, "title" TEXT NOT NULL, "body" TEXT NULL)"#,
+    ///     Post::create_table_sql()
+    /// );
+    ///
+    /// # tokio_test::block_on(async {
+    /// let pool = SqlitePool::connect(":memory:").await?;
+    /// sqlx::query(Post::create_table_sql()).execute(&pool).await?;
+    ///
+    /// let post = Post { post_id: 0, title: "hello".to_string(), body: None };
+    /// post.create(&pool).await?;
+    /// assert!(Post::by_id(&pool, 1).await?.is_some());
+    /// # Ok::<(), sqlx::Error>(())
+    /// # }).unwrap();
+    /// ```
+    fn create_table_sql() -> &'static str;
+
+    /// Returns the DDL dropping the table if it exists.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sqlx::{FromRow, SqlitePool};
+    /// use sqlx_crud::{Schema, SqlxCrud};
+    ///
+    /// #[derive(FromRow, SqlxCrud)]
+    /// struct User {
+    ///     user_id: i32,
+    ///     name: String,
+    /// }
+    ///
+    /// assert_eq!(r#"DROP TABLE IF EXISTS "users""#, User::drop_table_sql());
+    ///
+    /// # tokio_test::block_on(async {
+    /// let pool = SqlitePool::connect(":memory:").await?;
+    /// sqlx::query(User::create_table_sql()).execute(&pool).await?;
+    /// sqlx::query(User::drop_table_sql()).execute(&pool).await?;
+    ///
+    /// let tables: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM sqlite_master WHERE name = 'users'")
+    ///     .fetch_one(&pool)
+    ///     .await?;
+    /// assert_eq!(0, tables);
+    /// // Dropping a missing table is a no-op.
+    /// sqlx::query(User::drop_table_sql()).execute(&pool).await?;
+    /// # Ok::<(), sqlx::Error>(())
+    /// # }).unwrap();
+    /// ```
+    fn drop_table_sql() -> &'static str;
+}
+
+/// Lifecycle hooks run by the [Crud] methods writing to the database. Every
+/// hook is passed the executor of the operation and defaults to a no-op.
+///
+/// The `before_*` hooks receive the record before it is written and return
+/// it, possibly modified, to normalize values. Returning an error aborts the
+/// operation, nothing is written and the error is returned by the [Crud]
+/// method. The `after_*` hooks run once the record was written and receive
+/// its ID and the query result. An error from an `after_*` hook is returned
+/// by the [Crud] method, but the write has already happened.
+///
+/// [delete](Crud::delete), [delete_as](Crud::delete_as) and
+/// [force_delete](Crud::force_delete) run the delete hooks.
+///
+/// This trait is implemented with the default hooks by the [SqlxCrud] derive
+/// macro. Tag the struct with `#[hooks]` to implement it yourself.
+///
+/// # Example
+///
+/// ```rust
+/// use sqlx::{FromRow, SqlitePool};
+/// use sqlx_crud::traits::CrudFut;
+/// use sqlx_crud::{Crud, CrudHooks, Schema, SqlxCrud};
+///
+/// #[derive(FromRow, SqlxCrud)]
+/// #[hooks]
+/// struct Tag {
+///     #[id]
+///     #[auto_increment]
+///     tag_id: i64,
+///     name: String,
+/// }
+///
+/// impl<'e> CrudHooks<'e, &'e SqlitePool> for Tag {
+///     fn before_create(mut self, _pool: &'e SqlitePool) -> CrudFut<'e, Self> {
+///         self.name = self.name.trim().to_lowercase();
+///         if self.name.is_empty() {
+///             return Box::pin(async { Err(sqlx::Error::Protocol("tag name is empty".into())) });
+///         }
+///         Box::pin(async { Ok(self) })
+///     }
+/// }
+///
+/// # tokio_test::block_on(async {
+/// let pool = SqlitePool::connect(":memory:").await?;
+/// sqlx::query(Tag::create_table_sql()).execute(&pool).await?;
+///
+/// Tag { tag_id: 0, name: " Rust ".to_string() }.create(&pool).await?;
+/// assert_eq!("rust", Tag::by_id(&pool, 1).await?.unwrap().name);
+///
+/// assert!(Tag { tag_id: 0, name: " ".to_string() }.create(&pool).await.is_err());
+/// assert_eq!(1, Tag::all(&pool).await?.len());
+/// # Ok::<(), sqlx::Error>(())
+/// # }).unwrap();
+/// ```
+///
+/// [SqlxCrud]: ../derive.SqlxCrud.html
+pub trait CrudHooks<'e, E>
+where
+    Self: 'e + Sized + Send + Schema,
+    E: Executor<'e> + 'e,
+{
+    /// Runs before the record is inserted.
+    fn before_create(self, _executor: E) -> CrudFut<'e, Self> {
+        Box::pin(futures::future::ok(self))
+    }
+
+    /// Runs after the record was inserted. The ID is the one assigned by the
+    /// database for `#[auto_increment]` keys, otherwise the one of the record
+    /// passed to [create](Crud::create).
+    fn after_create<'r>(
+        _id: Self::Id,
+        _result: &'r <E::Database as Database>::QueryResult,
+        _executor: E,
+    ) -> CrudFut<'r, ()>
+    where
+        'e: 'r,
+    {
+        Box::pin(futures::future::ok(()))
+    }
+
+    /// Runs before the record is updated.
+    fn before_update(self, _executor: E) -> CrudFut<'e, Self> {
+        Box::pin(futures::future::ok(self))
+    }
+
+    /// Runs after the record was updated.
+    fn after_update<'r>(
+        _id: Self::Id,
+        _result: &'r <E::Database as Database>::QueryResult,
+        _executor: E,
+    ) -> CrudFut<'r, ()>
+    where
+        'e: 'r,
+    {
+        Box::pin(futures::future::ok(()))
+    }
+
+    /// Runs before the record is deleted.
+    fn before_delete(self, _executor: E) -> CrudFut<'e, Self> {
+        Box::pin(futures::future::ok(self))
+    }
+
+    /// Runs after the record was deleted.
+    fn after_delete<'r>(
+        _id: Self::Id,
+        _result: &'r <E::Database as Database>::QueryResult,
+        _executor: E,
+    ) -> CrudFut<'r, ()>
+    where
+        'e: 'r,
+    {
+        Box::pin(futures::future::ok(()))
+    }
 }
 
 /// Common Create, Read, Update, and Delete behaviors. This trait requires that
@@ -164,11 +666,19 @@ pub trait Schema {
 /// [SqlxCrud]: ../derive.SqlxCrud.html
 pub trait Crud<'e, E>
 where
-    Self: 'e + Sized + Send + Unpin + for<'r> FromRow<'r, <E::Database as Database>::Row> + Schema,
+    Self: 'e
+        + Sized
+        + Send
+        + Unpin
+        + for<'r> FromRow<'r, <E::Database as Database>::Row>
+        + Schema
+        + CrudHooks<'e, E>
+        + Validate,
     <Self as Schema>::Id:
-        Encode<'e, <E as Executor<'e>>::Database> + Type<<E as Executor<'e>>::Database>,
+        for<'q> Encode<'q, <E as Executor<'e>>::Database> + Type<<E as Executor<'e>>::Database>,
     E: Executor<'e> + 'e,
-    <E::Database as HasArguments<'e>>::Arguments: IntoArguments<'e, <E as Executor<'e>>::Database>,
+    for<'q> <E::Database as HasArguments<'q>>::Arguments:
+        IntoArguments<'q, <E as Executor<'e>>::Database>,
 {
     /// Returns an owned instance of [sqlx::Arguments]. self is consumed.
     /// Values in the fields are moved in to the `Arguments` instance.
@@ -180,8 +690,46 @@ where
     ///
     fn update_args(self) -> <E::Database as HasArguments<'e>>::Arguments;
 
+    /// Executes a write of the record `id` and resolves to its result and
+    /// the id of the record, which creates of `#[auto_increment]` models read
+    /// back from the database. Overridden by the derive macro for those
+    /// models and for audited and outbox models, which write the change
+    /// together with its audit log entry and outbox event in a transaction.
+    #[doc(hidden)]
+    fn execute_write(
+        pool: E,
+        _operation: Operation,
+        id: <Self as Schema>::Id,
+        query: Query<'e, E::Database, <E::Database as HasArguments<'e>>::Arguments>,
+    ) -> CrudFut<
+        'e,
+        (
+            <<E as Executor<'e>>::Database as Database>::QueryResult,
+            <Self as Schema>::Id,
+        ),
+    > {
+        Box::pin(query.execute(pool).map_ok(move |result| (result, id)))
+    }
+
+    /// Publishes a write of the record `id` to the [events](crate::events)
+    /// subscribers of the model. Overridden by the derive macro for
+    /// `#[crud(events)]` models.
+    #[doc(hidden)]
+    fn publish_event<'r>(
+        _pool: E,
+        _operation: Operation,
+        _id: <Self as Schema>::Id,
+        _result: &'r <E::Database as Database>::QueryResult,
+    ) -> CrudFut<'r, ()>
+    where
+        'e: 'r,
+    {
+        Box::pin(futures::future::ok(()))
+    }
+
     /// Returns a future that resolves to an insert or `sqlx::Error` of the
-    /// current instance.
+    /// current instance. Runs the create [hooks](CrudHooks) and
+    /// [validates](Validate) the record.
     ///
     /// # Example
     ///
@@ -197,10 +745,40 @@ where
     fn create(
         self,
         pool: E,
-    ) -> CrudFut<'e, <<E as Executor<'e>>::Database as Database>::QueryResult> {
-        Box::pin({
-            let args = self.insert_args();
-            ::sqlx::query_with::<E::Database, _>(Self::insert_sql(), args).execute(pool)
+    ) -> CrudFut<'e, <<E as Executor<'e>>::Database as Database>::QueryResult>
+    where
+        E: Copy,
+    {
+        Box::pin(self.create_with_id(pool).map_ok(|(result, _)| result))
+    }
+
+    /// Like [create](Crud::create), but also resolves to the ID of the
+    /// record, which is the one assigned by the database for
+    /// `#[auto_increment]` keys.
+    #[doc(hidden)]
+    fn create_with_id(
+        self,
+        pool: E,
+    ) -> CrudFut<
+        'e,
+        (
+            <<E as Executor<'e>>::Database as Database>::QueryResult,
+            <Self as Schema>::Id,
+        ),
+    >
+    where
+        E: Copy,
+    {
+        Box::pin(async move {
+            let record = self.before_create(pool).await?;
+            record.validate()?;
+            let id = record.id();
+            let args = record.insert_args();
+            let query = ::sqlx::query_with::<E::Database, _>(Self::insert_sql(), args);
+            let (result, id) = Self::execute_write(pool, Operation::Create, id, query).await?;
+            Self::publish_event(pool, Operation::Create, id, &result).await?;
+            <Self as CrudHooks<'e, E>>::after_create(id, &result, pool).await?;
+            Ok((result, id))
         })
     }
 
@@ -225,6 +803,299 @@ where
         stream.try_collect()
     }
 
+    /// Returns a [Select] of the records which can be sorted and paged before
+    /// it is run. See the [select](crate::select) module.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sqlx::{FromRow, SqlitePool};
+    /// use sqlx_crud::{Crud, Schema, SqlxCrud};
+    ///
+    /// #[derive(FromRow, SqlxCrud)]
+    /// struct User {
+    ///     user_id: i64,
+    ///     name: String,
+    /// }
+    ///
+    /// # tokio_test::block_on(async {
+    /// let pool = SqlitePool::connect(":memory:").await?;
+    /// sqlx::query(User::create_table_sql()).execute(&pool).await?;
+    /// for (user_id, name) in [(1, "carol"), (2, "alice"), (3, "bob")] {
+    ///     User { user_id, name: name.to_string() }.create(&pool).await?;
+    /// }
+    ///
+    /// let users: Vec<User> = User::query()
+    ///     .order_by(User::cols().name.asc())
+    ///     .limit(2)
+    ///     .fetch_all(&pool)
+    ///     .await?;
+    /// assert_eq!(vec![2, 3], users.iter().map(|u| u.user_id).collect::<Vec<_>>());
+    /// # Ok::<(), sqlx::Error>(())
+    /// # }).unwrap();
+    /// ```
+    fn query() -> Select<Self> {
+        Select::new()
+    }
+
+    /// Counts the records, skipping soft deleted records.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sqlx::{FromRow, SqlitePool};
+    /// use sqlx_crud::{Crud, Schema, SqlxCrud};
+    ///
+    /// #[derive(FromRow, SqlxCrud)]
+    /// struct User {
+    ///     user_id: i64,
+    ///     name: String,
+    /// }
+    ///
+    /// # tokio_test::block_on(async {
+    /// let pool = SqlitePool::connect(":memory:").await?;
+    /// sqlx::query(User::create_table_sql()).execute(&pool).await?;
+    /// User { user_id: 1, name: "alice".to_string() }.create(&pool).await?;
+    ///
+    /// assert_eq!(1, User::count(&pool).await?);
+    /// assert!(User::exists(&pool, 1).await?);
+    /// assert!(!User::exists(&pool, 2).await?);
+    /// # Ok::<(), sqlx::Error>(())
+    /// # }).unwrap();
+    /// ```
+    fn count(pool: E) -> CrudFut<'e, i64>
+    where
+        (i64,): for<'r> FromRow<'r, <E::Database as Database>::Row>,
+    {
+        let sql = format!(
+            "SELECT COUNT(*) FROM {}{}",
+            Self::dialect().quote_ident(Self::table_name()),
+            filter::where_clause::<Self>(None)
+        );
+        Box::pin(async move { sqlx::query_scalar(&sql).fetch_one(pool).await })
+    }
+
+    /// Counts the records matching `filter`, skipping soft deleted records.
+    /// See the [filter](crate::filter) module for an example.
+    fn count_where(pool: E, filter: Filter<'e, Self, E::Database>) -> CrudFut<'e, i64>
+    where
+        (i64,): for<'r> FromRow<'r, <E::Database as Database>::Row>,
+    {
+        let sql = format!(
+            "SELECT COUNT(*) FROM {}{}",
+            Self::dialect().quote_ident(Self::table_name()),
+            filter::where_clause::<Self>(Some(filter.sql()))
+        );
+        Box::pin(async move {
+            sqlx::query_scalar_with(&sql, filter.arguments())
+                .fetch_one(pool)
+                .await
+        })
+    }
+
+    /// Returns whether a record with the ID exists and isn't soft deleted.
+    /// See [count](Crud::count) for an example.
+    fn exists(pool: E, id: <Self as Schema>::Id) -> CrudFut<'e, bool>
+    where
+        (i64,): for<'r> FromRow<'r, <E::Database as Database>::Row>,
+    {
+        let dialect = Self::dialect();
+        let id_column = format!(
+            "{}.{} = ?",
+            dialect.quote_ident(Self::table_name()),
+            dialect.quote_ident(Self::id_column())
+        );
+        let sql = format!(
+            "SELECT COUNT(*) FROM {}{}",
+            dialect.quote_ident(Self::table_name()),
+            filter::where_clause::<Self>(Some(&id_column))
+        );
+        Box::pin(async move {
+            let count: i64 = sqlx::query_scalar(&sql).bind(id).fetch_one(pool).await?;
+            Ok(count > 0)
+        })
+    }
+
+    /// Returns the sum of `column` over the records which aren't soft
+    /// deleted, or `None` if there are none. The sum has the type of the
+    /// column. On MySQL, which can only cast it to a BIGINT, the sum of a
+    /// narrower integer column fails to decode if it doesn't fit.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sqlx::{FromRow, SqlitePool};
+    /// use sqlx_crud::{Crud, Schema, SqlxCrud};
+    ///
+    /// #[derive(FromRow, SqlxCrud)]
+    /// struct Page {
+    ///     page_id: i64,
+    ///     views: i64,
+    /// }
+    ///
+    /// # tokio_test::block_on(async {
+    /// let pool = SqlitePool::connect(":memory:").await?;
+    /// sqlx::query(Page::create_table_sql()).execute(&pool).await?;
+    /// assert_eq!(None, Page::sum(&pool, Page::cols().views).await?);
+    ///
+    /// for (page_id, views) in [(1, 10), (2, 3), (3, 7)] {
+    ///     Page { page_id, views }.create(&pool).await?;
+    /// }
+    ///
+    /// assert_eq!(Some(20), Page::sum(&pool, Page::cols().views).await?);
+    /// assert_eq!(Some(3), Page::min(&pool, Page::cols().views).await?);
+    /// assert_eq!(Some(10), Page::max(&pool, Page::cols().views).await?);
+    /// # Ok::<(), sqlx::Error>(())
+    /// # }).unwrap();
+    /// ```
+    fn sum<V>(pool: E, column: Column<Self, V>) -> CrudFut<'e, Option<V>>
+    where
+        V: 'e + Send + Unpin,
+        (Option<V>,): for<'r> FromRow<'r, <E::Database as Database>::Row>,
+    {
+        let sql = filter::aggregate_sql("SUM", column);
+        Box::pin(async move { sqlx::query_scalar(&sql).fetch_one(pool).await })
+    }
+
+    /// Returns the smallest value of `column` over the records which aren't
+    /// soft deleted, or `None` if there are none. See [sum](Crud::sum) for
+    /// an example.
+    fn min<V>(pool: E, column: Column<Self, V>) -> CrudFut<'e, Option<V>>
+    where
+        V: 'e + Send + Unpin,
+        (Option<V>,): for<'r> FromRow<'r, <E::Database as Database>::Row>,
+    {
+        let sql = filter::aggregate_sql("MIN", column);
+        Box::pin(async move { sqlx::query_scalar(&sql).fetch_one(pool).await })
+    }
+
+    /// Returns the largest value of `column` over the records which aren't
+    /// soft deleted, or `None` if there are none. See [sum](Crud::sum) for
+    /// an example.
+    fn max<V>(pool: E, column: Column<Self, V>) -> CrudFut<'e, Option<V>>
+    where
+        V: 'e + Send + Unpin,
+        (Option<V>,): for<'r> FromRow<'r, <E::Database as Database>::Row>,
+    {
+        let sql = filter::aggregate_sql("MAX", column);
+        Box::pin(async move { sqlx::query_scalar(&sql).fetch_one(pool).await })
+    }
+
+    /// Returns the first record matching `filter` in ID order, skipping soft
+    /// deleted records, or `None` if there is none.
+    fn first_where(pool: E, filter: Filter<'e, Self, E::Database>) -> CrudFut<'e, Option<Self>> {
+        let dialect = Self::dialect();
+        let (select, limit) = match dialect {
+            Dialect::Mssql => (
+                Self::select_with_deleted_sql().replacen("SELECT ", "SELECT TOP 1 ", 1),
+                "",
+            ),
+            _ => (Self::select_with_deleted_sql().to_string(), " LIMIT 1"),
+        };
+        let sql = format!(
+            "{}{} ORDER BY {}.{}{}",
+            select,
+            filter::where_clause::<Self>(Some(filter.sql())),
+            dialect.quote_ident(Self::table_name()),
+            dialect.quote_ident(Self::id_column()),
+            limit
+        );
+        Box::pin(async move {
+            sqlx::query_as_with::<E::Database, Self, _>(&sql, filter.arguments())
+                .fetch_optional(pool)
+                .await
+        })
+    }
+
+    /// Returns the first record matching `filter`, or creates the record
+    /// returned by `default` if there is none. The returned flag is `true`
+    /// if the record was created.
+    ///
+    /// Two callers can both miss the record and try to create it. With a
+    /// unique constraint on the filtered columns, the loser's insert fails,
+    /// and the record created by the winner is selected and returned instead
+    /// of the error. Without such a constraint both records are created. The
+    /// created record is selected again by its ID after the insert, so the
+    /// ID assigned to `#[auto_increment]` keys and the defaults set by the
+    /// database or the create [hooks](CrudHooks) are returned.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sqlx::{FromRow, SqlitePool};
+    /// use sqlx_crud::{Crud, Schema, SqlxCrud};
+    ///
+    /// #[derive(FromRow, SqlxCrud)]
+    /// struct Tag {
+    ///     tag_id: i64,
+    ///     name: String,
+    /// }
+    ///
+    /// # tokio_test::block_on(async {
+    /// let pool = SqlitePool::connect(":memory:").await?;
+    /// sqlx::query(Tag::create_table_sql()).execute(&pool).await?;
+    /// sqlx::query("CREATE UNIQUE INDEX tags_name ON tags (name)")
+    ///     .execute(&pool)
+    ///     .await?;
+    ///
+    /// let name = Tag::cols().name.eq("rust".to_string());
+    /// let rust = || Tag { tag_id: 1, name: "rust".to_string() };
+    /// let (tag, created) = Tag::find_or_create(&pool, name.clone(), rust).await?;
+    /// assert!(created);
+    /// assert_eq!(1, tag.tag_id);
+    ///
+    /// let (tag, created) = Tag::find_or_create(&pool, name, rust).await?;
+    /// assert!(!created);
+    /// assert_eq!(1, tag.tag_id);
+    /// assert_eq!(1, Tag::count(&pool).await?);
+    /// # Ok::<(), sqlx::Error>(())
+    /// # }).unwrap();
+    /// ```
+    fn find_or_create<F>(
+        pool: E,
+        filter: Filter<'e, Self, E::Database>,
+        default: F,
+    ) -> CrudFut<'e, (Self, bool)>
+    where
+        E: Copy,
+        F: 'e + Send + FnOnce() -> Self,
+    {
+        Box::pin(async move {
+            if let Some(record) = Self::first_where(pool, filter.clone()).await? {
+                return Ok((record, false));
+            }
+
+            let id = match default().create_with_id(pool).await {
+                Ok((_, id)) => id,
+                Err(error) if filter::is_unique_violation(&error, Self::dialect()) => {
+                    return match Self::first_where(pool, filter).await? {
+                        Some(record) => Ok((record, false)),
+                        None => Err(error),
+                    };
+                }
+                Err(error) => return Err(error),
+            };
+
+            match Self::by_id(pool, id).await? {
+                Some(record) => Ok((record, true)),
+                None => Err(sqlx::Error::RowNotFound),
+            }
+        })
+    }
+
+    /// Returns the first record matching `filter`, or creates `record` if
+    /// there is none. See [find_or_create](Crud::find_or_create).
+    fn first_or_insert(
+        pool: E,
+        filter: Filter<'e, Self, E::Database>,
+        record: Self,
+    ) -> CrudFut<'e, (Self, bool)>
+    where
+        E: Copy,
+    {
+        Self::find_or_create(pool, filter, move || record)
+    }
+
     #[doc(hidden)]
     fn paged(_pool: E) -> TryCollectFut<'e, Self> {
         unimplemented!()
@@ -260,8 +1131,283 @@ where
         })
     }
 
+    /// Looks up a record by ID inside the transaction `tx` and locks it until
+    /// the transaction ends, waiting for other transactions holding the
+    /// lock. See the [lock](crate::lock) module for an example and the
+    /// SQLite fallback.
+    fn by_id_for_update<'t>(
+        tx: &'t mut Transaction<'_, E::Database>,
+        id: <Self as Schema>::Id,
+    ) -> CrudFut<'t, Option<Self>>
+    where
+        'e: 't,
+        for<'c> &'c mut <E::Database as Database>::Connection: Executor<'c, Database = E::Database>,
+    {
+        let dialect = Self::dialect();
+        let condition = format!(
+            "{}.{} = ?",
+            dialect.quote_ident(Self::table_name()),
+            dialect.quote_ident(Self::id_column())
+        );
+        let sql = lock::lock_sql::<Self>(Lock::Wait, &condition, None);
+        Box::pin(lock::fetch_by_id::<E::Database, Self>(&mut **tx, sql, id))
+    }
+
+    /// Locks at most `limit` records matching `filter` inside the transaction
+    /// `tx`, in ID order, skipping the records locked by other transactions.
+    /// See the [lock](crate::lock) module for an example.
+    fn lock_next<'t>(
+        tx: &'t mut Transaction<'_, E::Database>,
+        filter: Filter<'e, Self, E::Database>,
+        limit: u64,
+    ) -> CrudFut<'t, Vec<Self>>
+    where
+        'e: 't,
+        for<'c> &'c mut <E::Database as Database>::Connection: Executor<'c, Database = E::Database>,
+    {
+        Self::lock_where(tx, filter, Some(limit), Lock::SkipLocked)
+    }
+
+    /// Locks the records matching `filter` inside the transaction `tx`, at
+    /// most `limit` of them in ID order, treating records locked by other
+    /// transactions according to `lock`.
+    fn lock_where<'t>(
+        tx: &'t mut Transaction<'_, E::Database>,
+        filter: Filter<'e, Self, E::Database>,
+        limit: Option<u64>,
+        lock: Lock,
+    ) -> CrudFut<'t, Vec<Self>>
+    where
+        'e: 't,
+        for<'c> &'c mut <E::Database as Database>::Connection: Executor<'c, Database = E::Database>,
+    {
+        let sql = lock::lock_sql::<Self>(lock, filter.sql(), limit);
+        Box::pin(lock::fetch_where(&mut **tx, sql, filter))
+    }
+
+    /// Adds `by` to `column` of the record with the ID in a single UPDATE,
+    /// so concurrent increments aren't lost. Soft deleted records aren't
+    /// changed. Like a raw query, it doesn't run the [hooks](CrudHooks) and
+    /// isn't recorded in the [audit log](crate::audit), the
+    /// [outbox](crate::outbox) or the [events](crate::events).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sqlx::{FromRow, SqlitePool};
+    /// use sqlx_crud::{Crud, Schema, SqlxCrud};
+    ///
+    /// #[derive(FromRow, SqlxCrud)]
+    /// struct Page {
+    ///     page_id: i64,
+    ///     views: i64,
+    /// }
+    ///
+    /// # tokio_test::block_on(async {
+    /// let pool = SqlitePool::connect(":memory:").await?;
+    /// sqlx::query(Page::create_table_sql()).execute(&pool).await?;
+    /// Page { page_id: 1, views: 0 }.create(&pool).await?;
+    ///
+    /// let views = Page::cols().views;
+    /// assert_eq!(1, Page::increment(&pool, 1, views, 5).await?.rows_affected());
+    /// assert_eq!(0, Page::increment(&pool, 2, views, 5).await?.rows_affected());
+    /// assert_eq!(Some(3), Page::decrement_returning(&pool, 1, views, 2).await?);
+    /// assert_eq!(None, Page::increment_returning(&pool, 2, views, 1).await?);
+    /// # Ok::<(), sqlx::Error>(())
+    /// # }).unwrap();
+    /// ```
+    fn increment<V>(
+        pool: E,
+        id: <Self as Schema>::Id,
+        column: Column<Self, V>,
+        by: V,
+    ) -> CrudFut<'e, <<E as Executor<'e>>::Database as Database>::QueryResult>
+    where
+        V: 'e + Send + for<'q> Encode<'q, E::Database> + Type<E::Database>,
+    {
+        Self::add_to_counter("+", pool, id, column, by)
+    }
+
+    /// Subtracts `by` from `column` of the record with the ID in a single
+    /// UPDATE. See [increment](Crud::increment).
+    fn decrement<V>(
+        pool: E,
+        id: <Self as Schema>::Id,
+        column: Column<Self, V>,
+        by: V,
+    ) -> CrudFut<'e, <<E as Executor<'e>>::Database as Database>::QueryResult>
+    where
+        V: 'e + Send + for<'q> Encode<'q, E::Database> + Type<E::Database>,
+    {
+        Self::add_to_counter("-", pool, id, column, by)
+    }
+
+    /// Like [increment](Crud::increment), but resolves to the new value of
+    /// the column, or `None` if no record was changed. Fails on MySQL, which
+    /// has no RETURNING clause.
+    fn increment_returning<V>(
+        pool: E,
+        id: <Self as Schema>::Id,
+        column: Column<Self, V>,
+        by: V,
+    ) -> CrudFut<'e, Option<V>>
+    where
+        V: 'e + Send + Unpin + for<'q> Encode<'q, E::Database> + Type<E::Database>,
+        (V,): for<'r> FromRow<'r, <E::Database as Database>::Row>,
+    {
+        Self::add_to_counter_returning("+", pool, id, column, by)
+    }
+
+    /// Like [decrement](Crud::decrement), but resolves to the new value of
+    /// the column, or `None` if no record was changed. Fails on MySQL, which
+    /// has no RETURNING clause.
+    fn decrement_returning<V>(
+        pool: E,
+        id: <Self as Schema>::Id,
+        column: Column<Self, V>,
+        by: V,
+    ) -> CrudFut<'e, Option<V>>
+    where
+        V: 'e + Send + Unpin + for<'q> Encode<'q, E::Database> + Type<E::Database>,
+        (V,): for<'r> FromRow<'r, <E::Database as Database>::Row>,
+    {
+        Self::add_to_counter_returning("-", pool, id, column, by)
+    }
+
+    #[doc(hidden)]
+    fn add_to_counter<V>(
+        operator: &str,
+        pool: E,
+        id: <Self as Schema>::Id,
+        column: Column<Self, V>,
+        by: V,
+    ) -> CrudFut<'e, <<E as Executor<'e>>::Database as Database>::QueryResult>
+    where
+        V: 'e + Send + for<'q> Encode<'q, E::Database> + Type<E::Database>,
+    {
+        match filter::counter_sql(operator, column, false) {
+            Ok(sql) => {
+                Box::pin(async move { sqlx::query(&sql).bind(by).bind(id).execute(pool).await })
+            }
+            Err(e) => Box::pin(futures::future::err(e)),
+        }
+    }
+
+    #[doc(hidden)]
+    fn add_to_counter_returning<V>(
+        operator: &str,
+        pool: E,
+        id: <Self as Schema>::Id,
+        column: Column<Self, V>,
+        by: V,
+    ) -> CrudFut<'e, Option<V>>
+    where
+        V: 'e + Send + Unpin + for<'q> Encode<'q, E::Database> + Type<E::Database>,
+        (V,): for<'r> FromRow<'r, <E::Database as Database>::Row>,
+    {
+        match filter::counter_sql(operator, column, true) {
+            Ok(sql) => Box::pin(async move {
+                sqlx::query_scalar(&sql)
+                    .bind(by)
+                    .bind(id)
+                    .fetch_optional(pool)
+                    .await
+            }),
+            Err(e) => Box::pin(futures::future::err(e)),
+        }
+    }
+
+    /// Sets the columns of `patch` on every record matching `filter` in a
+    /// single UPDATE and resolves to its result, whose `rows_affected` is
+    /// the number of records changed. Soft deleted records aren't changed.
+    /// Like [increment](Crud::increment) it doesn't run the
+    /// [hooks](CrudHooks), validate the records or set `#[updated_at]`
+    /// fields, and isn't recorded in the audit log, the outbox or the
+    /// events.
+    ///
+    /// Fails with [sqlx::Error::Configuration] if `patch` is empty, or if
+    /// `filter` matches every record and wasn't built with
+    /// [Filter::allow_all].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sqlx::{FromRow, SqlitePool};
+    /// use sqlx_crud::filter::{Filter, Patch};
+    /// use sqlx_crud::{Crud, Schema, SqlxCrud};
+    ///
+    /// #[derive(FromRow, SqlxCrud)]
+    /// struct Task {
+    ///     task_id: i64,
+    ///     done: bool,
+    ///     #[deleted_with(flag)]
+    ///     archived: bool,
+    /// }
+    ///
+    /// # tokio_test::block_on(async {
+    /// let pool = SqlitePool::connect(":memory:").await?;
+    /// sqlx::query(Task::create_table_sql()).execute(&pool).await?;
+    /// for task_id in 1..=4 {
+    ///     Task { task_id, done: false, archived: false }.create(&pool).await?;
+    /// }
+    ///
+    /// let cols = Task::cols();
+    /// let patch = Patch::new().set(cols.done, true);
+    /// let result = Task::update_where(&pool, cols.task_id.le(2), patch).await?;
+    /// assert_eq!(2, result.rows_affected());
+    ///
+    /// let result = Task::delete_where(&pool, cols.done.eq(true)).await?;
+    /// assert_eq!(2, result.rows_affected());
+    /// assert_eq!(2, Task::count(&pool).await?);
+    ///
+    /// assert!(Task::delete_where(&pool, Filter::all()).await.is_err());
+    /// let result = Task::delete_where(&pool, Filter::all().allow_all()).await?;
+    /// assert_eq!(2, result.rows_affected());
+    /// # Ok::<(), sqlx::Error>(())
+    /// # }).unwrap();
+    /// ```
+    fn update_where(
+        pool: E,
+        filter: Filter<'e, Self, E::Database>,
+        patch: Patch<'e, Self, E::Database>,
+    ) -> CrudFut<'e, <<E as Executor<'e>>::Database as Database>::QueryResult> {
+        match filter::update_where_sql(&filter, &patch) {
+            Ok(sql) => Box::pin(async move {
+                let mut args = patch.arguments();
+                filter.bind(&mut args);
+                sqlx::query_with(&sql, args).execute(pool).await
+            }),
+            Err(e) => Box::pin(futures::future::err(e)),
+        }
+    }
+
+    /// Deletes every record matching `filter` in a single statement and
+    /// resolves to its result. Models with a `#[deleted_with]` field are
+    /// soft deleted like by [delete](Crud::delete), without setting a
+    /// `#[deleted_by]` field, and records already soft deleted aren't
+    /// counted. The hooks, audit log, outbox and events are skipped as by
+    /// [update_where](Crud::update_where), which also has an example.
+    ///
+    /// Fails with [sqlx::Error::Configuration] if `filter` matches every
+    /// record and wasn't built with [Filter::allow_all].
+    fn delete_where(
+        pool: E,
+        filter: Filter<'e, Self, E::Database>,
+    ) -> CrudFut<'e, <<E as Executor<'e>>::Database as Database>::QueryResult> {
+        match filter::delete_where_sql(&filter) {
+            Ok(sql) => Box::pin(async move {
+                sqlx::query_with(&sql, filter.arguments())
+                    .execute(pool)
+                    .await
+            }),
+            Err(e) => Box::pin(futures::future::err(e)),
+        }
+    }
+
     /// Updates the database with the current instance state and returns a
     /// future that resolves to the new `Self` returned from the database.
+    /// Runs the update [hooks](CrudHooks) and [validates](Validate) the
+    /// record.
     ///
     /// # Example
     ///
@@ -284,15 +1430,35 @@ where
     fn update(
         self,
         pool: E,
-    ) -> CrudFut<'e, <<E as Executor<'e>>::Database as Database>::QueryResult> {
-        Box::pin({
-            let args = self.update_args();
-            ::sqlx::query_with::<E::Database, _>(Self::update_by_id_sql(), args).execute(pool)
+    ) -> CrudFut<'e, <<E as Executor<'e>>::Database as Database>::QueryResult>
+    where
+        E: Copy,
+    {
+        Box::pin(async move {
+            let record = self.before_update(pool).await?;
+            record.validate()?;
+            let id = record.id();
+            let args = record.update_args();
+            let query = ::sqlx::query_with::<E::Database, _>(Self::update_by_id_sql(), args);
+            let (result, _) = Self::execute_write(pool, Operation::Update, id, query).await?;
+            Self::publish_event(pool, Operation::Update, id, &result).await?;
+            <Self as CrudHooks<'e, E>>::after_update(id, &result, pool).await?;
+            Ok(result)
         })
     }
 
     /// Deletes a record from the database by ID and returns a future that
-    /// resolves to `()` on success or `sqlx::Error` on failure.
+    /// resolves to `()` on success or `sqlx::Error` on failure. Runs the
+    /// delete [hooks](CrudHooks).
+    ///
+    /// Models with a `#[deleted_with]` field are soft deleted instead, the
+    /// row is kept and marked as deleted:
+    ///
+    /// * `#[deleted_with(now)]` sets an `Option<T>` field to the current
+    ///   timestamp of the database.
+    /// * `#[deleted_with(flag)]` sets a `bool` field to true.
+    /// * `#[deleted_with = "..."]` sets the field to an SQL expression, which
+    ///   is used as is. `"now()"` is the same as `#[deleted_with(now)]`.
     ///
     /// # Example
     ///
@@ -309,8 +1475,261 @@ where
     fn delete(
         self,
         pool: E,
+    ) -> CrudFut<'e, <<E as Executor<'e>>::Database as Database>::QueryResult>
+    where
+        E: Copy,
+    {
+        Box::pin(async move {
+            let id = self.before_delete(pool).await?.id();
+            let query = sqlx::query(<Self as Schema>::delete_by_id_sql()).bind(id);
+            let (result, _) = Self::execute_write(pool, Operation::Delete, id, query).await?;
+            Self::publish_event(pool, Operation::Delete, id, &result).await?;
+            <Self as CrudHooks<'e, E>>::after_delete(id, &result, pool).await?;
+            Ok(result)
+        })
+    }
+
+    /// Soft deletes a record by ID like [delete](Crud::delete) and binds
+    /// `actor` to the `#[deleted_by]` field, e.g. the ID of the user
+    /// deleting the record. [restore](Crud::restore) clears the field again.
+    /// Fails with [sqlx::Error::Configuration] if the model has no
+    /// `#[deleted_by]` field.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sqlx::{FromRow, SqlitePool};
+    /// use sqlx_crud::{Crud, Schema, SqlxCrud};
+    ///
+    /// #[derive(FromRow, SqlxCrud)]
+    /// struct Note {
+    ///     #[id]
+    ///     #[auto_increment]
+    ///     note_id: i64,
+    ///     body: String,
+    ///     #[ignore_when(insert, update)]
+    ///     #[deleted_with(now)]
+    ///     deleted_at: Option<String>,
+    ///     #[ignore_when(insert, update)]
+    ///     #[deleted_by]
+    ///     deleted_by: Option<i64>,
+    /// }
+    ///
+    /// # tokio_test::block_on(async {
+    /// let pool = SqlitePool::connect(":memory:").await?;
+    /// sqlx::query(Note::create_table_sql()).execute(&pool).await?;
+    ///
+    /// let note = Note { note_id: 0, body: "hello".to_string(), deleted_at: None, deleted_by: None };
+    /// note.create(&pool).await?;
+    /// Note::by_id(&pool, 1).await?.unwrap().delete_as(&pool, 42).await?;
+    ///
+    /// let deleted = &Note::only_deleted(&pool).await?[0];
+    /// assert!(deleted.deleted_at.is_some());
+    /// assert_eq!(Some(42), deleted.deleted_by);
+    /// # Ok::<(), sqlx::Error>(())
+    /// # }).unwrap();
+    /// ```
+    fn delete_as<A>(
+        self,
+        pool: E,
+        actor: A,
+    ) -> CrudFut<'e, <<E as Executor<'e>>::Database as Database>::QueryResult>
+    where
+        E: Copy,
+        A: 'e + Send + Encode<'e, E::Database> + Type<E::Database>,
+    {
+        Box::pin(async move {
+            let sql = <Self as Schema>::delete_as_by_id_sql().ok_or_else(|| {
+                sqlx::Error::Configuration(
+                    format!(
+                        "{} has no #[deleted_by] field",
+                        <Self as Schema>::table_name()
+                    )
+                    .into(),
+                )
+            })?;
+            let id = self.before_delete(pool).await?.id();
+            let query = sqlx::query(sql).bind(actor).bind(id);
+            let (result, _) = Self::execute_write(pool, Operation::Delete, id, query).await?;
+            Self::publish_event(pool, Operation::Delete, id, &result).await?;
+            <Self as CrudHooks<'e, E>>::after_delete(id, &result, pool).await?;
+            Ok(result)
+        })
+    }
+
+    /// Permanently deletes a record from the database by ID. Unlike
+    /// [delete](Crud::delete) this removes the row even when the model is
+    /// soft deleted with `#[deleted_with]`. Runs the delete
+    /// [hooks](CrudHooks).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sqlx::{FromRow, SqlitePool};
+    /// use sqlx_crud::{Crud, Schema, SqlxCrud};
+    ///
+    /// #[derive(FromRow, SqlxCrud)]
+    /// struct Note {
+    ///     note_id: i64,
+    ///     #[deleted_with(flag)]
+    ///     deleted: bool,
+    /// }
+    ///
+    /// # tokio_test::block_on(async {
+    /// let pool = SqlitePool::connect(":memory:").await?;
+    /// sqlx::query(Note::create_table_sql()).execute(&pool).await?;
+    /// Note { note_id: 1, deleted: false }.create(&pool).await?;
+    ///
+    /// Note::by_id(&pool, 1).await?.unwrap().delete(&pool).await?;
+    /// let note = Note::only_deleted(&pool).await?.pop().unwrap();
+    /// assert_eq!(1, note.force_delete(&pool).await?.rows_affected());
+    ///
+    /// let rows: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM notes")
+    ///     .fetch_one(&pool)
+    ///     .await?;
+    /// assert_eq!(0, rows);
+    /// # Ok::<(), sqlx::Error>(())
+    /// # }).unwrap();
+    /// ```
+    fn force_delete(
+        self,
+        pool: E,
+    ) -> CrudFut<'e, <<E as Executor<'e>>::Database as Database>::QueryResult>
+    where
+        E: Copy,
+    {
+        Box::pin(async move {
+            let id = self.before_delete(pool).await?.id();
+            let query = sqlx::query(<Self as Schema>::force_delete_by_id_sql()).bind(id);
+            let (result, _) = Self::execute_write(pool, Operation::Delete, id, query).await?;
+            Self::publish_event(pool, Operation::Delete, id, &result).await?;
+            <Self as CrudHooks<'e, E>>::after_delete(id, &result, pool).await?;
+            Ok(result)
+        })
+    }
+
+    /// Queries all records from the table, including soft deleted ones.
+    /// Resolves to the same records as [all](Crud::all) for models without
+    /// `#[deleted_with]`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sqlx::{FromRow, SqlitePool};
+    /// use sqlx_crud::{Crud, Schema, SqlxCrud};
+    ///
+    /// #[derive(FromRow, SqlxCrud)]
+    /// struct Note {
+    ///     #[id]
+    ///     #[auto_increment]
+    ///     note_id: i64,
+    ///     body: String,
+    ///     #[ignore_when(insert, update)]
+    ///     #[deleted_with = "CURRENT_TIMESTAMP"]
+    ///     deleted_at: Option<String>,
+    /// }
+    ///
+    /// # tokio_test::block_on(async {
+    /// let pool = SqlitePool::connect(":memory:").await?;
+    /// sqlx::query(Note::create_table_sql()).execute(&pool).await?;
+    ///
+    /// for body in ["kept", "deleted"] {
+    ///     Note { note_id: 0, body: body.to_string(), deleted_at: None }.create(&pool).await?;
+    /// }
+    /// Note::by_id(&pool, 2).await?.unwrap().delete(&pool).await?;
+    ///
+    /// assert_eq!(1, Note::all(&pool).await?.len());
+    /// assert_eq!(2, Note::all_with_deleted(&pool).await?.len());
+    /// assert_eq!("deleted", Note::only_deleted(&pool).await?[0].body);
+    ///
+    /// Note::restore(&pool, 2).await?;
+    /// assert_eq!(2, Note::all(&pool).await?.len());
+    /// assert!(Note::only_deleted(&pool).await?.is_empty());
+    /// # Ok::<(), sqlx::Error>(())
+    /// # }).unwrap();
+    /// ```
+    fn all_with_deleted(pool: E) -> TryCollectFut<'e, Self> {
+        let stream =
+            sqlx::query_as::<E::Database, Self>(<Self as Schema>::select_with_deleted_sql())
+                .fetch(pool);
+        stream.try_collect()
+    }
+
+    /// Queries the soft deleted records from the table. Resolves to an empty
+    /// `Vec` for models without `#[deleted_with]`.
+    ///
+    /// See [all_with_deleted](Crud::all_with_deleted) for an example.
+    fn only_deleted(pool: E) -> TryCollectFut<'e, Self> {
+        let stream: CrudStream<'e, Self> = match <Self as Schema>::select_only_deleted_sql() {
+            Some(sql) => sqlx::query_as::<E::Database, Self>(sql).fetch(pool),
+            None => Box::pin(futures::stream::empty()),
+        };
+        stream.try_collect()
+    }
+
+    /// Restores a soft deleted record by ID. The query result reports no
+    /// affected rows if the record isn't deleted or the model has no
+    /// `#[deleted_with]` field.
+    ///
+    /// See [all_with_deleted](Crud::all_with_deleted) for an example.
+    fn restore(
+        pool: E,
+        id: <Self as Schema>::Id,
     ) -> CrudFut<'e, <<E as Executor<'e>>::Database as Database>::QueryResult> {
-        let query = sqlx::query(<Self as Schema>::delete_by_id_sql()).bind(self.id());
-        Box::pin(query.execute(pool))
+        match <Self as Schema>::restore_by_id_sql() {
+            Some(sql) => Box::pin(sqlx::query(sql).bind(id).execute(pool)),
+            None => Box::pin(futures::future::ok(Default::default())),
+        }
+    }
+
+    /// Permanently deletes the records soft deleted before `timestamp`. The
+    /// timestamp is bound as is, so it should have the type of the
+    /// `#[deleted_with]` field. The query result reports no affected rows
+    /// for models without `#[deleted_with]` or soft deleted with
+    /// `#[deleted_with(flag)]`, which doesn't record when a row was deleted.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sqlx::{FromRow, SqlitePool};
+    /// use sqlx_crud::{Crud, Schema, SqlxCrud};
+    ///
+    /// #[derive(FromRow, SqlxCrud)]
+    /// struct Note {
+    ///     #[id]
+    ///     #[auto_increment]
+    ///     note_id: i64,
+    ///     body: String,
+    ///     #[ignore_when(insert, update)]
+    ///     #[deleted_with = "'2000-01-01 00:00:00'"]
+    ///     deleted_at: Option<String>,
+    /// }
+    ///
+    /// # tokio_test::block_on(async {
+    /// let pool = SqlitePool::connect(":memory:").await?;
+    /// sqlx::query(Note::create_table_sql()).execute(&pool).await?;
+    ///
+    /// Note { note_id: 0, body: "old".to_string(), deleted_at: None }.create(&pool).await?;
+    /// Note::by_id(&pool, 1).await?.unwrap().delete(&pool).await?;
+    ///
+    /// let r = Note::purge_deleted_before(&pool, "1999-12-31 00:00:00").await?;
+    /// assert_eq!(0, r.rows_affected());
+    /// let r = Note::purge_deleted_before(&pool, "2000-01-02 00:00:00").await?;
+    /// assert_eq!(1, r.rows_affected());
+    /// assert!(Note::all_with_deleted(&pool).await?.is_empty());
+    /// # Ok::<(), sqlx::Error>(())
+    /// # }).unwrap();
+    /// ```
+    fn purge_deleted_before<T>(
+        pool: E,
+        timestamp: T,
+    ) -> CrudFut<'e, <<E as Executor<'e>>::Database as Database>::QueryResult>
+    where
+        T: 'e + Send + Encode<'e, E::Database> + Type<E::Database>,
+    {
+        match <Self as Schema>::purge_deleted_sql() {
+            Some(sql) => Box::pin(sqlx::query(sql).bind(timestamp).execute(pool)),
+            None => Box::pin(futures::future::ok(Default::default())),
+        }
     }
 }
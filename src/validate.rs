@@ -0,0 +1,241 @@
+//! Field validation run before records are written.
+//!
+//! Fields are checked with the `#[validate(...)]` attribute:
+//!
+//! * `length(min = 1, max = 255)` checks the number of characters of a
+//!   string or the number of items of a `Vec`.
+//! * `range(min = 0, max = 100)` checks a number, or any other `PartialOrd`
+//!   value. The bounds have to be literals of the field's type.
+//! * `regex = "..."` checks a string matches the regular expression. The
+//!   pattern is checked by the derive macro, an invalid one fails the build,
+//!   and compiled once on the first check.
+//!
+//! `None` values of `Option<T>` fields aren't checked. The derive macro
+//! implements [Validate] with the checks, and [create](crate::Crud::create)
+//! and [update](crate::Crud::update) run them after the `before_*`
+//! [hooks](crate::CrudHooks) and before writing anything. A failed check is
+//! returned as [ValidationErrors] listing every field that failed, wrapped in
+//! a [sqlx::Error::Configuration] like the other requests refused before
+//! reaching the database. It is never a [sqlx::Error::Database], so it isn't
+//! mistaken for a constraint violation. [ValidationErrors::from_error] gets
+//! it back.
+//!
+//! # Example
+//!
+//! ```rust
+//! use sqlx::{FromRow, SqlitePool};
+//! use sqlx_crud::validate::ValidationErrors;
+//! use sqlx_crud::{Crud, Schema, SqlxCrud, Validate};
+//!
+//! #[derive(FromRow, SqlxCrud)]
+//! struct Account {
+//!     #[id]
+//!     #[auto_increment]
+//!     account_id: i64,
+//!     #[validate(length(min = 1, max = 32), regex = "^[a-z0-9_]+$")]
+//!     login: String,
+//!     #[validate(range(min = 13))]
+//!     age: Option<i64>,
+//! }
+//!
+//! let account = Account { account_id: 0, login: "Not Valid".to_string(), age: Some(7) };
+//! let errors = account.validate().unwrap_err();
+//! assert_eq!(
+//!     vec![("login", "regex"), ("age", "range")],
+//!     errors.errors().iter().map(|e| (e.field, e.code)).collect::<Vec<_>>()
+//! );
+//!
+//! # tokio_test::block_on(async {
+//! let pool = SqlitePool::connect(":memory:").await?;
+//! sqlx::query(Account::create_table_sql()).execute(&pool).await?;
+//!
+//! let error = account.create(&pool).await.unwrap_err();
+//! assert!(error.as_database_error().is_none());
+//! let errors = ValidationErrors::from_error(&error).unwrap();
+//! assert_eq!("age must be at least 13", errors.field("age").next().unwrap().message);
+//! assert!(Account::all(&pool).await?.is_empty());
+//!
+//! Account { account_id: 0, login: "valid_login".to_string(), age: None }.create(&pool).await?;
+//! # Ok::<(), sqlx::Error>(())
+//! # }).unwrap();
+//! ```
+//!
+//! ```rust,compile_fail
+//! # use sqlx::FromRow;
+//! # use sqlx_crud::SqlxCrud;
+//! #[derive(FromRow, SqlxCrud)]
+//! struct Account {
+//!     account_id: i64,
+//!     #[validate(regex = "^[a-z")]
+//!     login: String,
+//! }
+//! ```
+
+use std::error::Error as StdError;
+use std::fmt;
+
+#[doc(hidden)]
+pub use regex::Regex;
+
+/// Checks the values of a record. Implemented by the [SqlxCrud] derive macro
+/// from the `#[validate(...)]` field attributes.
+///
+/// [SqlxCrud]: ../derive.SqlxCrud.html
+pub trait Validate {
+    /// Returns every failed check, or `Ok` if the record is valid.
+    fn validate(&self) -> Result<(), ValidationErrors>;
+}
+
+/// A failed check of a single field.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldError {
+    /// Name of the field.
+    pub field: &'static str,
+    /// The failed check: `"length"`, `"range"` or `"regex"`.
+    pub code: &'static str,
+    /// Description of the failure, e.g. `age must be at least 13`.
+    pub message: String,
+}
+
+/// The failed checks of a record.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValidationErrors {
+    message: String,
+    errors: Vec<FieldError>,
+}
+
+impl ValidationErrors {
+    pub fn new(errors: Vec<FieldError>) -> Self {
+        let message = errors
+            .iter()
+            .map(|e| e.message.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        Self { message, errors }
+    }
+
+    /// Returns the failed checks in the order of the fields.
+    pub fn errors(&self) -> &[FieldError] {
+        &self.errors
+    }
+
+    /// Returns the failed checks of the field `name`.
+    pub fn field<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a FieldError> + 'a {
+        self.errors.iter().filter(move |e| e.field == name)
+    }
+
+    /// Returns the validation errors wrapped in an error returned by a
+    /// [Crud](crate::Crud) method, or `None` for any other error.
+    pub fn from_error(error: &sqlx::Error) -> Option<&Self> {
+        match error {
+            sqlx::Error::Configuration(error) => error.downcast_ref(),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "validation failed: {}", self.message)
+    }
+}
+
+impl StdError for ValidationErrors {}
+
+impl From<ValidationErrors> for sqlx::Error {
+    fn from(errors: ValidationErrors) -> Self {
+        sqlx::Error::Configuration(Box::new(errors))
+    }
+}
+
+/// Values with a length checked by `#[validate(length(...))]`.
+pub trait Length {
+    fn length(&self) -> usize;
+}
+
+impl Length for str {
+    fn length(&self) -> usize {
+        self.chars().count()
+    }
+}
+
+impl Length for String {
+    fn length(&self) -> usize {
+        self.as_str().length()
+    }
+}
+
+impl<T> Length for [T] {
+    fn length(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<T> Length for Vec<T> {
+    fn length(&self) -> usize {
+        self.len()
+    }
+}
+
+#[doc(hidden)]
+pub fn length<T: Length + ?Sized>(
+    field: &'static str,
+    value: &T,
+    min: Option<usize>,
+    max: Option<usize>,
+) -> Option<FieldError> {
+    let length = value.length();
+    let message = match (min, max) {
+        (Some(min), _) if length < min => {
+            format!("{} must have a length of at least {}", field, min)
+        }
+        (_, Some(max)) if length > max => {
+            format!("{} must have a length of at most {}", field, max)
+        }
+        _ => return None,
+    };
+
+    Some(FieldError {
+        field,
+        code: "length",
+        message,
+    })
+}
+
+#[doc(hidden)]
+pub fn range<T: PartialOrd + fmt::Display>(
+    field: &'static str,
+    value: &T,
+    min: Option<T>,
+    max: Option<T>,
+) -> Option<FieldError> {
+    let message = match (min, max) {
+        (Some(min), _) if *value < min => format!("{} must be at least {}", field, min),
+        (_, Some(max)) if *value > max => format!("{} must be at most {}", field, max),
+        _ => return None,
+    };
+
+    Some(FieldError {
+        field,
+        code: "range",
+        message,
+    })
+}
+
+#[doc(hidden)]
+pub fn regex<T: AsRef<str> + ?Sized>(
+    field: &'static str,
+    value: &T,
+    regex: &Regex,
+) -> Option<FieldError> {
+    if regex.is_match(value.as_ref()) {
+        return None;
+    }
+
+    Some(FieldError {
+        field,
+        code: "regex",
+        message: format!("{} must match {}", field, regex.as_str()),
+    })
+}
@@ -0,0 +1,126 @@
+//! Writes of the [Crud](crate::Crud) methods which record the change in the
+//! [audit log](crate::audit) or the [outbox](crate::outbox) in the same
+//! transaction.
+
+use std::fmt;
+
+use futures::TryStreamExt;
+use serde::Serialize;
+use sqlx::database::HasArguments;
+use sqlx::query::Query;
+use sqlx::{
+    ColumnIndex, Database, Decode, Either, Encode, Executor, FromRow, IntoArguments, Pool, Row,
+    Type,
+};
+
+use crate::traits::{Operation, Schema};
+use crate::{audit, outbox};
+
+/// Executes `query`, a write of the record `id` of `T`, in a transaction
+/// together with its audit log entry and outbox event. `select_sql` selects
+/// the record by id, including soft deleted rows. Resolves to the result and
+/// the id of the record, see [run]. `rows_affected` reads the number of rows
+/// changed from the result, writes changing none aren't recorded.
+pub async fn execute<'e, DB, T>(
+    pool: &'e Pool<DB>,
+    operation: Operation,
+    id: T::Id,
+    query: Query<'e, DB, <DB as HasArguments<'e>>::Arguments>,
+    select_sql: &'static str,
+    inserted_id: fn(T::Id, &DB::QueryResult) -> T::Id,
+    rows_affected: fn(&DB::QueryResult) -> u64,
+) -> Result<(DB::QueryResult, T::Id), sqlx::Error>
+where
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> <DB as HasArguments<'q>>::Arguments: IntoArguments<'q, DB>,
+    for<'q> String: Encode<'q, DB> + Type<DB>,
+    for<'q> Option<String>: Encode<'q, DB>,
+    T: Schema + Serialize + Send + Unpin + for<'r> FromRow<'r, DB::Row>,
+    for<'q> T::Id: fmt::Display + Encode<'q, DB> + Decode<'q, DB> + Type<DB>,
+    usize: ColumnIndex<DB::Row>,
+{
+    let mut tx = pool.begin().await?;
+    let old_values = match operation {
+        Operation::Create => None,
+        _ => values::<DB, T>(&mut tx, select_sql, id).await?,
+    };
+    let (result, id) = run::<DB, T, _>(&mut *tx, operation, id, query, inserted_id).await?;
+    let new_values = values::<DB, T>(&mut tx, select_sql, id).await?;
+    let changed = rows_affected(&result) > 0;
+
+    // Nothing is recorded when the record exists neither before nor after
+    // the write.
+    if let Some(payload) = new_values.clone().or_else(|| old_values.clone()) {
+        if T::audited() && changed {
+            audit::insert::<DB, T>(&mut tx, id.to_string(), operation, old_values, new_values)
+                .await?;
+        }
+        if T::outbox() && changed {
+            outbox::insert::<DB, T>(&mut tx, id.to_string(), operation, payload).await?;
+        }
+    }
+
+    tx.commit().await?;
+    Ok((result, id))
+}
+
+/// Executes `query`, a write of the record `id` of `T`, and resolves to its
+/// result and the id of the record. Creates of records whose primary key is
+/// assigned by the database read it from the row returned by the insert on
+/// Postgres and MSSQL, and with `inserted_id` from the result elsewhere.
+pub async fn run<'q, 'c, DB, T, X>(
+    executor: X,
+    operation: Operation,
+    id: T::Id,
+    query: Query<'q, DB, <DB as HasArguments<'q>>::Arguments>,
+    inserted_id: fn(T::Id, &DB::QueryResult) -> T::Id,
+) -> Result<(DB::QueryResult, T::Id), sqlx::Error>
+where
+    DB: Database,
+    X: Executor<'c, Database = DB>,
+    <DB as HasArguments<'q>>::Arguments: IntoArguments<'q, DB>,
+    T: Schema,
+    for<'r> T::Id: Decode<'r, DB> + Type<DB>,
+    usize: ColumnIndex<DB::Row>,
+{
+    if operation != Operation::Create {
+        return Ok((query.execute(executor).await?, id));
+    }
+
+    let mut result = DB::QueryResult::default();
+    let mut returned_id = None;
+    let mut steps = query.fetch_many(executor);
+    while let Some(step) = steps.try_next().await? {
+        match step {
+            Either::Left(step) => result.extend(Some(step)),
+            Either::Right(row) => returned_id = Some(row.try_get(0)?),
+        }
+    }
+    let id = returned_id.unwrap_or_else(|| inserted_id(id, &result));
+    Ok((result, id))
+}
+
+/// Returns the record `id` selected with `select_sql` as JSON.
+async fn values<DB, T>(
+    conn: &mut DB::Connection,
+    select_sql: &'static str,
+    id: T::Id,
+) -> Result<Option<String>, sqlx::Error>
+where
+    DB: Database,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> <DB as HasArguments<'q>>::Arguments: IntoArguments<'q, DB>,
+    T: Schema + Serialize + Send + Unpin + for<'r> FromRow<'r, DB::Row>,
+    for<'q> T::Id: Encode<'q, DB> + Type<DB>,
+{
+    let record = sqlx::query_as::<DB, T>(select_sql)
+        .bind(id)
+        .fetch_optional(conn)
+        .await?;
+
+    record
+        .map(|r| serde_json::to_string(&r))
+        .transpose()
+        .map_err(|e| sqlx::Error::Protocol(e.to_string()))
+}
@@ -0,0 +1,86 @@
+use sqlx::{FromRow, SqlitePool};
+use sqlx_crud::{Crud, Schema, SqlxCrud};
+
+#[derive(Debug, FromRow, SqlxCrud)]
+struct Page {
+    page_id: i64,
+    views: i32,
+    score: f64,
+    #[deleted_with(flag)]
+    deleted: bool,
+}
+
+async fn pages() -> Result<SqlitePool, sqlx::Error> {
+    let pool = SqlitePool::connect(":memory:").await?;
+    sqlx::query(Page::create_table_sql()).execute(&pool).await?;
+    for (page_id, views, score) in [(1, 10, 0.5), (2, 3, 2.0), (3, 7, 1.5), (4, 100, 9.0)] {
+        Page {
+            page_id,
+            views,
+            score,
+            deleted: false,
+        }
+        .create(&pool)
+        .await?;
+    }
+    Page::by_id(&pool, 4).await?.unwrap().delete(&pool).await?;
+    Ok(pool)
+}
+
+#[tokio::test]
+async fn count_skips_soft_deleted_records() -> Result<(), sqlx::Error> {
+    let pool = pages().await?;
+
+    assert_eq!(3, Page::count(&pool).await?);
+    assert_eq!(1, Page::only_deleted(&pool).await?.len());
+    Ok(())
+}
+
+#[tokio::test]
+async fn count_where_skips_soft_deleted_records() -> Result<(), sqlx::Error> {
+    let pool = pages().await?;
+    let cols = Page::cols();
+
+    assert_eq!(2, Page::count_where(&pool, cols.views.gt(5)).await?);
+    assert_eq!(0, Page::count_where(&pool, cols.page_id.eq(4)).await?);
+    let filter = cols.views.lt(5).or(cols.page_id.is_in(vec![1, 4]));
+    assert_eq!(2, Page::count_where(&pool, filter.clone()).await?);
+    assert_eq!(2, Page::count_where(&pool, filter).await?);
+    Ok(())
+}
+
+#[tokio::test]
+async fn exists_skips_soft_deleted_records() -> Result<(), sqlx::Error> {
+    let pool = pages().await?;
+
+    assert!(Page::exists(&pool, 1).await?);
+    assert!(!Page::exists(&pool, 4).await?);
+    assert!(!Page::exists(&pool, 5).await?);
+    Ok(())
+}
+
+#[tokio::test]
+async fn aggregates_skip_soft_deleted_records() -> Result<(), sqlx::Error> {
+    let pool = pages().await?;
+    let cols = Page::cols();
+
+    assert_eq!(Some(20), Page::sum(&pool, cols.views).await?);
+    assert_eq!(Some(3), Page::min(&pool, cols.views).await?);
+    assert_eq!(Some(10), Page::max(&pool, cols.views).await?);
+    assert_eq!(Some(4.0), Page::sum(&pool, cols.score).await?);
+    assert_eq!(Some(2.0), Page::max(&pool, cols.score).await?);
+    Ok(())
+}
+
+#[tokio::test]
+async fn aggregates_of_no_records_are_none() -> Result<(), sqlx::Error> {
+    let pool = pages().await?;
+    let cols = Page::cols();
+    Page::delete_where(&pool, cols.page_id.le(3)).await?;
+
+    assert_eq!(0, Page::count(&pool).await?);
+    assert_eq!(None, Page::sum(&pool, cols.views).await?);
+    assert_eq!(None, Page::min(&pool, cols.score).await?);
+    assert_eq!(None, Page::max(&pool, cols.views).await?);
+    Ok(())
+}
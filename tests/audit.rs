@@ -0,0 +1,49 @@
+use serde::Serialize;
+use sqlx::{FromRow, SqlitePool};
+use sqlx_crud::{audit, Crud, Operation, Schema, SqlxCrud};
+
+#[derive(Debug, FromRow, Serialize, SqlxCrud)]
+#[crud(audited)]
+struct Note {
+    note_id: i64,
+    body: String,
+    #[deleted_with(flag)]
+    deleted: bool,
+}
+
+#[tokio::test]
+async fn writes_of_soft_deleted_records_are_not_recorded() -> Result<(), sqlx::Error> {
+    let pool = SqlitePool::connect(":memory:").await?;
+    sqlx::query(Note::create_table_sql()).execute(&pool).await?;
+    sqlx::query(&audit::create_table_sql(Note::dialect()))
+        .execute(&pool)
+        .await?;
+
+    Note {
+        note_id: 1,
+        body: "draft".to_string(),
+        deleted: false,
+    }
+    .create(&pool)
+    .await?;
+    Note::by_id(&pool, 1).await?.unwrap().delete(&pool).await?;
+    let operations = vec![Operation::Create, Operation::Delete];
+    let history = audit::history::<Note, _>(&pool, 1).await?;
+    assert_eq!(
+        operations,
+        history.iter().map(|e| e.operation).collect::<Vec<_>>()
+    );
+
+    let mut note = Note::only_deleted(&pool).await?.pop().unwrap();
+    note.body = "edited".to_string();
+    assert_eq!(0, note.update(&pool).await?.rows_affected());
+    let note = Note::only_deleted(&pool).await?.pop().unwrap();
+    assert_eq!(0, note.delete(&pool).await?.rows_affected());
+
+    let history = audit::history::<Note, _>(&pool, 1).await?;
+    assert_eq!(
+        operations,
+        history.iter().map(|e| e.operation).collect::<Vec<_>>()
+    );
+    Ok(())
+}
@@ -0,0 +1,58 @@
+#![cfg(feature = "cli")]
+
+use sqlx::FromRow;
+use sqlx_crud::cli::{self, Error};
+use sqlx_crud::SqlxCrud;
+
+// The models are only registered, never constructed.
+#[allow(dead_code)]
+#[derive(FromRow, SqlxCrud)]
+#[database(sqlite)]
+struct Bookmark {
+    bookmark_id: i64,
+    url: String,
+}
+
+#[allow(dead_code)]
+#[derive(FromRow, SqlxCrud)]
+#[database(postgres)]
+struct Invoice {
+    invoice_id: i64,
+    total: i64,
+}
+
+fn migrate_args(source: &str, options: &str) -> Vec<String> {
+    let mut args = vec!["migrate", "init", "--source", source];
+    args.extend(options.split_whitespace());
+    args.into_iter().map(String::from).collect()
+}
+
+#[tokio::test]
+async fn migrate_requires_a_dialect_when_models_use_several() {
+    let dir = std::env::temp_dir().join("sqlx-crud-cli-mixed");
+    let source = dir.to_str().unwrap();
+
+    let result = cli::run(migrate_args(source, "")).await;
+    assert!(matches!(result, Err(Error::Usage(_))));
+}
+
+#[tokio::test]
+async fn migrate_plans_the_models_of_the_selected_dialect() -> Result<(), Error> {
+    let dir = std::env::temp_dir().join("sqlx-crud-cli-dialect");
+    let _ = std::fs::remove_dir_all(&dir);
+    let source = dir.to_str().unwrap();
+
+    cli::run(migrate_args(source, "--dialect postgres")).await?;
+
+    let migration = std::fs::read_dir(&dir)?
+        .map(|entry| entry.unwrap().path())
+        .find(|path| path.extension().is_some_and(|e| e == "sql"))
+        .unwrap();
+    let sql = std::fs::read_to_string(migration)?;
+    assert!(sql.contains(r#"CREATE TABLE IF NOT EXISTS "invoices""#));
+    assert!(!sql.contains("bookmarks"));
+
+    let result = cli::run(migrate_args(source, "--dialect mssql")).await;
+    assert!(matches!(result, Err(Error::NoModelsFor(_))));
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use sqlx::{FromRow, SqlitePool};
+use sqlx_crud::codegen;
+use sqlx_crud::introspect::TableColumn;
+use sqlx_crud::schema::Dialect;
+use sqlx_crud::{Crud, Schema, SqlxCrud};
+
+fn column(name: &str, data_type: &str, primary_key: bool) -> TableColumn {
+    TableColumn {
+        name: name.to_string(),
+        data_type: data_type.to_string(),
+        nullable: false,
+        has_default: false,
+        primary_key,
+    }
+}
+
+#[test]
+fn escapes_keyword_and_invalid_column_names() {
+    let columns = vec![
+        column("id", "INTEGER", true),
+        column("type", "TEXT", false),
+        column("self", "TEXT", false),
+        column("first name", "TEXT", false),
+        column("last-name", "TEXT", false),
+        column("2fa", "BOOLEAN", false),
+    ];
+
+    assert_eq!(
+        r#"#[derive(Debug, FromRow, SqlxCrud)]
+#[database(sqlite)]
+pub struct Contact {
+    #[id]
+    #[auto_increment]
+    pub id: i64,
+    pub r#type: String,
+    #[sqlx(rename = "self")]
+    pub self_: String,
+    #[sqlx(rename = "first name")]
+    pub first_name: String,
+    #[sqlx(rename = "last-name")]
+    pub last_name: String,
+    #[sqlx(rename = "2fa")]
+    pub _2fa: bool,
+}
+"#,
+        codegen::model_source(Dialect::Sqlite, "contacts", &columns)
+    );
+}
+
+// The struct generated above, which must compile and map to the columns.
+#[derive(Debug, FromRow, SqlxCrud)]
+#[database(sqlite)]
+pub struct Contact {
+    #[id]
+    #[auto_increment]
+    pub id: i64,
+    pub r#type: String,
+    #[sqlx(rename = "self")]
+    pub self_: String,
+    #[sqlx(rename = "first name")]
+    pub first_name: String,
+    #[sqlx(rename = "last-name")]
+    pub last_name: String,
+    #[sqlx(rename = "2fa")]
+    pub _2fa: bool,
+}
+
+#[tokio::test]
+async fn generated_names_read_and_write_the_columns() -> Result<(), sqlx::Error> {
+    assert_eq!(
+        ["id", "type", "self", "first name", "last-name", "2fa"],
+        Contact::columns()
+    );
+
+    let pool = SqlitePool::connect(":memory:").await?;
+    sqlx::query(Contact::create_table_sql())
+        .execute(&pool)
+        .await?;
+    let contact = Contact {
+        id: 0,
+        r#type: "person".to_string(),
+        self_: "me".to_string(),
+        first_name: "Ada".to_string(),
+        last_name: "Lovelace".to_string(),
+        _2fa: true,
+    };
+    contact.create(&pool).await?;
+
+    let mut contact = Contact::by_id(&pool, 1).await?.unwrap();
+    assert_eq!("person", contact.r#type);
+    assert_eq!("Ada", contact.first_name);
+    assert!(contact._2fa);
+
+    contact.last_name = "King".to_string();
+    contact.update(&pool).await?;
+    let contact = Contact::by_id(&pool, 1).await?.unwrap();
+    assert_eq!("King", contact.last_name);
+    Ok(())
+}
@@ -0,0 +1,44 @@
+use sqlx::{FromRow, SqlitePool};
+use sqlx_crud::{events, Crud, Operation, Schema, SqlxCrud};
+use tokio::sync::broadcast::error::TryRecvError;
+
+#[derive(Debug, FromRow, SqlxCrud)]
+#[crud(events)]
+struct Message {
+    #[id]
+    #[auto_increment]
+    message_id: i64,
+    body: String,
+}
+
+#[tokio::test]
+async fn writes_matching_no_row_are_not_published() -> Result<(), sqlx::Error> {
+    let pool = SqlitePool::connect(":memory:").await?;
+    sqlx::query(Message::create_table_sql())
+        .execute(&pool)
+        .await?;
+    let mut messages = events::subscribe::<Message>();
+
+    let missing = Message {
+        message_id: 7,
+        body: "missing".to_string(),
+    };
+    assert_eq!(0, missing.update(&pool).await?.rows_affected());
+    let missing = Message {
+        message_id: 7,
+        body: "missing".to_string(),
+    };
+    assert_eq!(0, missing.delete(&pool).await?.rows_affected());
+    assert!(matches!(messages.try_recv(), Err(TryRecvError::Empty)));
+
+    Message {
+        message_id: 0,
+        body: "hello".to_string(),
+    }
+    .create(&pool)
+    .await?;
+    let created = messages.recv().await.unwrap();
+    assert_eq!((Operation::Create, 1), (created.op, created.id));
+    assert!(matches!(messages.try_recv(), Err(TryRecvError::Empty)));
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use sqlx::{FromRow, SqlitePool};
+use sqlx_crud::{Crud, Schema, SqlxCrud};
+
+#[derive(Debug, FromRow, SqlxCrud)]
+struct Tag {
+    #[id]
+    #[auto_increment]
+    tag_id: i64,
+    name: String,
+}
+
+fn tag(name: &str) -> Tag {
+    Tag {
+        tag_id: 0,
+        name: name.to_string(),
+    }
+}
+
+async fn tags() -> Result<SqlitePool, sqlx::Error> {
+    let pool = SqlitePool::connect(":memory:").await?;
+    sqlx::query(Tag::create_table_sql()).execute(&pool).await?;
+    Ok(pool)
+}
+
+#[tokio::test]
+async fn first_where_returns_the_lowest_id() -> Result<(), sqlx::Error> {
+    let pool = tags().await?;
+    for name in ["rust", "sql", "rust"] {
+        tag(name).create(&pool).await?;
+    }
+
+    let first = Tag::first_where(&pool, Tag::cols().name.eq("rust".to_string())).await?;
+    assert_eq!(1, first.unwrap().tag_id);
+    let first = Tag::first_where(&pool, Tag::cols().name.eq("go".to_string())).await?;
+    assert!(first.is_none());
+    Ok(())
+}
+
+#[tokio::test]
+async fn find_or_create_returns_the_inserted_record() -> Result<(), sqlx::Error> {
+    let pool = tags().await?;
+    tag("sql").create(&pool).await?;
+
+    let name = Tag::cols().name.eq("rust".to_string());
+    let (created, was_created) = Tag::find_or_create(&pool, name.clone(), || tag("rust")).await?;
+    assert!(was_created);
+    assert_eq!((2, "rust"), (created.tag_id, created.name.as_str()));
+
+    let (found, was_created) = Tag::find_or_create(&pool, name, || tag("rust")).await?;
+    assert!(!was_created);
+    assert_eq!(2, found.tag_id);
+    Ok(())
+}
+
+#[tokio::test]
+async fn find_or_create_selects_the_created_record_by_id() -> Result<(), sqlx::Error> {
+    let pool = tags().await?;
+
+    // The created record doesn't match the filter, it is still returned.
+    let name = Tag::cols().name.eq("Rust".to_string());
+    let (created, was_created) = Tag::first_or_insert(&pool, name, tag("rust")).await?;
+    assert!(was_created);
+    assert_eq!((1, "rust"), (created.tag_id, created.name.as_str()));
+    Ok(())
+}
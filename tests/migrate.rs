@@ -0,0 +1,55 @@
+use sqlx::{FromRow, SqlitePool};
+use sqlx_crud::migrate::{self, PlanError, Snapshot};
+use sqlx_crud::{Crud, SqlxCrud};
+
+#[derive(Debug, FromRow, SqlxCrud)]
+struct Article {
+    article_id: i64,
+    title: String,
+    views: i64,
+    published: bool,
+    summary: Option<String>,
+}
+
+#[tokio::test]
+async fn adds_not_null_columns_to_a_populated_table() -> Result<(), Box<dyn std::error::Error>> {
+    let pool = SqlitePool::connect(":memory:").await?;
+    sqlx::query("CREATE TABLE articles (article_id INTEGER PRIMARY KEY, title TEXT NOT NULL)")
+        .execute(&pool)
+        .await?;
+    sqlx::query("INSERT INTO articles (article_id, title) VALUES (1, 'hello')")
+        .execute(&pool)
+        .await?;
+
+    let models = sqlx_crud::models();
+    let current = Snapshot::from_database(&pool, &models).await?;
+    let statements = migrate::plan(&models, &current)?;
+    assert_eq!(3, statements.len());
+    for statement in &statements {
+        sqlx::query(statement).execute(&pool).await?;
+    }
+
+    let article = Article::by_id(&pool, 1).await?.unwrap();
+    assert_eq!("hello", article.title);
+    assert_eq!(0, article.views);
+    assert!(!article.published);
+    assert_eq!(None, article.summary);
+
+    let current = Snapshot::from_database(&pool, &models).await?;
+    assert!(migrate::plan(&models, &current)?.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn refuses_to_add_a_primary_key() {
+    let models = sqlx_crud::models();
+    let current = Snapshot::parse("table articles\ncolumn title\n");
+
+    match migrate::plan(&models, &current) {
+        Err(PlanError::PrimaryKey { table, column }) => {
+            assert_eq!("articles", table);
+            assert_eq!("article_id", column);
+        }
+        other => panic!("expected a primary key error, got {:?}", other),
+    }
+}
@@ -0,0 +1,51 @@
+use serde::Serialize;
+use sqlx::{FromRow, SqlitePool};
+use sqlx_crud::outbox::{self, OutboxRelay};
+use sqlx_crud::{Crud, Operation, Schema, SqlxCrud};
+
+#[derive(Debug, FromRow, Serialize, SqlxCrud)]
+#[crud(outbox)]
+struct Order {
+    order_id: i64,
+    total: i64,
+    #[deleted_with(flag)]
+    deleted: bool,
+}
+
+#[tokio::test]
+async fn writes_of_soft_deleted_records_insert_no_event() -> Result<(), Box<dyn std::error::Error>>
+{
+    let pool = SqlitePool::connect(":memory:").await?;
+    sqlx::query(Order::create_table_sql())
+        .execute(&pool)
+        .await?;
+    sqlx::query(&outbox::create_table_sql(Order::dialect()))
+        .execute(&pool)
+        .await?;
+
+    Order {
+        order_id: 1,
+        total: 100,
+        deleted: false,
+    }
+    .create(&pool)
+    .await?;
+    Order::by_id(&pool, 1).await?.unwrap().delete(&pool).await?;
+
+    let mut order = Order::only_deleted(&pool).await?.pop().unwrap();
+    order.total = 150;
+    assert_eq!(0, order.update(&pool).await?.rows_affected());
+    let order = Order::only_deleted(&pool).await?.pop().unwrap();
+    assert_eq!(0, order.delete(&pool).await?.rows_affected());
+
+    let mut operations = Vec::new();
+    let relay = OutboxRelay::new(pool.clone(), Order::dialect());
+    relay
+        .relay_once(|event| {
+            operations.push(event.operation);
+            async { Ok::<(), std::io::Error>(()) }
+        })
+        .await?;
+    assert_eq!(vec![Operation::Create, Operation::Delete], operations);
+    Ok(())
+}
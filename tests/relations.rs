@@ -0,0 +1,43 @@
+use sqlx::{FromRow, SqlitePool};
+use sqlx_crud::{Crud, Schema, SqlxCrud};
+
+#[derive(Debug, FromRow, SqlxCrud)]
+struct User {
+    user_id: i64,
+    name: String,
+}
+
+#[derive(Debug, FromRow, SqlxCrud)]
+struct Post {
+    post_id: i64,
+    #[belongs_to(User)]
+    user_id: i64,
+}
+
+#[tokio::test]
+async fn load_returns_each_parent_once() -> Result<(), sqlx::Error> {
+    let pool = SqlitePool::connect(":memory:").await?;
+    sqlx::query(User::create_table_sql()).execute(&pool).await?;
+    for (user_id, name) in [(1, "alice"), (2, "bob")] {
+        User {
+            user_id,
+            name: name.to_string(),
+        }
+        .create(&pool)
+        .await?;
+    }
+
+    // More ids than fit in a single IN query.
+    let posts = (0..1500)
+        .map(|post_id| Post {
+            post_id,
+            user_id: post_id % 2 + 1,
+        })
+        .collect::<Vec<_>>();
+    let users = Post::load_users(&pool, &posts).await?;
+    assert_eq!(
+        vec![1, 2],
+        users.iter().map(|u| u.user_id).collect::<Vec<_>>()
+    );
+    Ok(())
+}
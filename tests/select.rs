@@ -0,0 +1,78 @@
+use sqlx::{FromRow, SqlitePool};
+use sqlx_crud::select::Select;
+use sqlx_crud::{Crud, Schema, SqlxCrud};
+
+#[derive(Debug, FromRow, SqlxCrud)]
+struct Author {
+    author_id: i64,
+    #[sqlx(rename = "full_name")]
+    name: String,
+}
+
+#[derive(Debug, PartialEq, FromRow, SqlxCrud)]
+struct Book {
+    book_id: i64,
+    author_id: i64,
+    #[sqlx(rename = "book_title")]
+    title: String,
+    #[sqlx(try_from = "i64")]
+    pages: i32,
+}
+
+async fn books() -> Result<SqlitePool, sqlx::Error> {
+    let pool = SqlitePool::connect(":memory:").await?;
+    sqlx::query(Author::create_table_sql())
+        .execute(&pool)
+        .await?;
+    sqlx::query(Book::create_table_sql()).execute(&pool).await?;
+
+    Author {
+        author_id: 1,
+        name: "Ursula".to_string(),
+    }
+    .create(&pool)
+    .await?;
+    Book {
+        book_id: 1,
+        author_id: 1,
+        title: "Lathe".to_string(),
+        pages: 184,
+    }
+    .create(&pool)
+    .await?;
+    Ok(pool)
+}
+
+#[tokio::test]
+async fn joins_decode_renamed_columns() -> Result<(), sqlx::Error> {
+    let pool = books().await?;
+
+    let select = Select::<Book>::new().join::<Author>("books.author_id = authors.author_id");
+    let (book, author) = select.fetch_all(&pool).await?.remove(0);
+    assert_eq!(("Lathe", 184), (book.title.as_str(), book.pages));
+    assert_eq!("Ursula", author.name);
+    Ok(())
+}
+
+#[tokio::test]
+async fn query_returns_the_records_of_all() -> Result<(), sqlx::Error> {
+    let pool = books().await?;
+
+    assert_eq!(
+        Book::all(&pool).await?,
+        Book::query().fetch_all(&pool).await?
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn try_from_fields_are_converted() -> Result<(), sqlx::Error> {
+    let pool = books().await?;
+    sqlx::query("UPDATE books SET pages = ?")
+        .bind(i64::MAX)
+        .execute(&pool)
+        .await?;
+
+    assert!(Book::query().fetch_all(&pool).await.is_err());
+    Ok(())
+}
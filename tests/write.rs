@@ -0,0 +1,98 @@
+use sqlx::sqlite::SqliteQueryResult;
+use sqlx::{FromRow, SqlitePool};
+use sqlx_crud::traits::CrudFut;
+use sqlx_crud::{write, Crud, CrudHooks, Operation, Schema, SqlxCrud};
+
+#[derive(Debug, FromRow, SqlxCrud)]
+#[hooks]
+struct Ticket {
+    #[id]
+    #[auto_increment]
+    ticket_id: i64,
+    title: String,
+}
+
+impl<'e> CrudHooks<'e, &'e SqlitePool> for Ticket {
+    fn after_create<'r>(
+        id: i64,
+        _result: &'r SqliteQueryResult,
+        pool: &'e SqlitePool,
+    ) -> CrudFut<'r, ()>
+    where
+        'e: 'r,
+    {
+        Box::pin(async move {
+            sqlx::query("INSERT INTO created (ticket_id) VALUES (?)")
+                .bind(id)
+                .execute(pool)
+                .await?;
+            Ok(())
+        })
+    }
+}
+
+// Postgres and MSSQL models are only compiled, there is no server to run on.
+#[cfg(feature = "cli")]
+#[allow(dead_code)]
+#[derive(FromRow, SqlxCrud)]
+#[database(postgres)]
+struct Invoice {
+    #[id]
+    #[auto_increment]
+    invoice_id: i64,
+    total: i64,
+}
+
+#[cfg(feature = "cli")]
+#[test]
+fn postgres_inserts_return_the_assigned_id() {
+    assert_eq!(
+        r#"INSERT INTO "invoices" ("total") VALUES (?) RETURNING "invoice_id""#,
+        Invoice::insert_sql()
+    );
+}
+
+#[tokio::test]
+async fn creates_pass_the_assigned_id_to_hooks() -> Result<(), sqlx::Error> {
+    let pool = SqlitePool::connect(":memory:").await?;
+    sqlx::query(Ticket::create_table_sql())
+        .execute(&pool)
+        .await?;
+    sqlx::query("CREATE TABLE created (ticket_id INTEGER NOT NULL)")
+        .execute(&pool)
+        .await?;
+
+    for title in ["first", "second"] {
+        Ticket {
+            ticket_id: 0,
+            title: title.to_string(),
+        }
+        .create(&pool)
+        .await?;
+    }
+
+    let created: Vec<i64> = sqlx::query_scalar("SELECT ticket_id FROM created")
+        .fetch_all(&pool)
+        .await?;
+    assert_eq!(vec![1, 2], created);
+    Ok(())
+}
+
+#[tokio::test]
+async fn run_reads_the_id_returned_by_the_insert() -> Result<(), sqlx::Error> {
+    let pool = SqlitePool::connect(":memory:").await?;
+    sqlx::query(Ticket::create_table_sql())
+        .execute(&pool)
+        .await?;
+    sqlx::query("INSERT INTO tickets (ticket_id, title) VALUES (41, 'earlier')")
+        .execute(&pool)
+        .await?;
+
+    let query =
+        sqlx::query("INSERT INTO tickets (title) VALUES (?) RETURNING ticket_id").bind("returned");
+    let (result, id) =
+        write::run::<_, Ticket, _>(&pool, Operation::Create, 0, query, |id, _| id).await?;
+    assert_eq!(42, id);
+    assert_eq!(1, result.rows_affected());
+    Ok(())
+}